@@ -1,4 +1,85 @@
-use three_d::{CpuMesh, InnerSpace, Mat4, Quat, Rotation3, SquareMatrix, Vec3, radians};
+use three_d::{
+    Context, CpuMesh, EuclideanSpace, Gm, InnerSpace, InstancedMesh, Instances, Mat4,
+    PhysicalMaterial, Point3, Srgba, Vec3, rotation_matrix_from_dir_to_dir,
+};
+
+use crate::curves::FrenetFrame;
+
+/// Size and colours of the arrows drawn for each vector of a [`FrenetFrame`].
+#[derive(Clone, Copy, Debug)]
+pub struct FrameArrowStyle {
+    pub length: f32,
+    pub thickness: f32,
+    pub tangent_color: Srgba,
+    pub normal_color: Srgba,
+    pub binormal_color: Srgba,
+}
+
+impl Default for FrameArrowStyle {
+    fn default() -> Self {
+        Self {
+            length: 0.18,
+            thickness: 0.007,
+            tangent_color: Srgba::new_opaque(255, 255, 0),
+            normal_color: Srgba::new_opaque(190, 100, 0),
+            binormal_color: Srgba::GREEN,
+        }
+    }
+}
+
+/// Builds one instanced arrow mesh each for the tangents, normals and binormals
+/// of `frame`, with an arrow per station in `points`.
+pub fn frame_arrows(
+    context: &Context,
+    points: &[Vec3],
+    frame: &FrenetFrame,
+    style: &FrameArrowStyle,
+) -> [Gm<InstancedMesh, PhysicalMaterial>; 3] {
+    let arrow = CpuMesh::arrow(0.9, 0.5, 16);
+
+    [
+        (&frame.tangents, style.tangent_color),
+        (&frame.normals, style.normal_color),
+        (&frame.binormals, style.binormal_color),
+    ]
+    .map(|(directions, color)| {
+        Gm::new(
+            InstancedMesh::new(
+                context,
+                &arrow_transformations(points, directions, style.length, style.thickness),
+                &arrow,
+            ),
+            PhysicalMaterial {
+                albedo: color,
+                ..Default::default()
+            },
+        )
+    })
+}
+
+/// Places a unit arrow along x at every point, pointing in the matching direction.
+pub fn arrow_transformations(
+    points: &[Vec3],
+    directions: &[Vec3],
+    length: f32,
+    thickness: f32,
+) -> Instances {
+    Instances {
+        transformations: points
+            .iter()
+            .zip(directions)
+            .map(|(point, direction)| {
+                Mat4::from_translation(*point)
+                    * rotation_matrix_from_dir_to_dir(
+                        Point3::new(1.0, 0.0, 0.0).to_vec(),
+                        direction.normalize(),
+                    )
+                    * Mat4::from_nonuniform_scale(length, thickness, thickness)
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
 
 pub fn two_points(start: Vec3, direction: Vec3, distance: f32) -> (CpuMesh, CpuMesh) {
     let mut start_sphere = CpuMesh::sphere(8);
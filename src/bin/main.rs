@@ -1,36 +1,673 @@
+use clap::{Parser, builder::RangedU64ValueParser};
 use rene::{
-    curves::SineCurve,
-    tube::Tube,
-    wireframe::{edge_transformations, vertex_transformations},
+    arrow::{FrameArrowStyle, frame_arrows},
+    bvh::Bvh,
+    curves::{Curve, SineCurve},
+    export::{ExportFormat, export_mesh},
+    fly_through::FlyThrough,
+    gizmo::{Gizmo, GizmoMode},
+    glb::{GlbNode, export_glb},
+    headless::OffscreenContext,
+    history::{Command, History},
+    lights::{LightSettings, LightType, SceneLight, default_lights},
+    loader::{FileContents, read_file},
+    model::{LoadedModel, ModelNode, ModelPart, ModelTransform},
+    navigation::{Navigation, NavigationMode},
+    pick::{PickHit, PickTarget, pick},
+    project::{DisplayOptions, Project, ProjectCurve, ProjectModel, load_project, save_project},
+    ray::Ray,
+    settings::{ViewerSettings, load_settings, save_settings},
+    snapshot::{GifWriter, render_offscreen, save_png, sequence_path, turntable_camera},
+    spline::{Spline, SplineType},
+    tube::{Tube, TubeParameters},
+    view::{
+        CameraPose, CameraTransition, FIELD_OF_VIEW_Y, Projection, StandardView, Z_FAR, Z_NEAR,
+        frame_bounds,
+    },
+    wireframe::{
+        edge_mesh, edge_transform, edge_transformations, vertex_mesh, vertex_transformations,
+    },
+};
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::mpsc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
-use std::path::Path;
 
 use three_d::{
-    AmbientLight, Axes, Camera, ClearState, ColorMaterial, Context, CpuMaterial, CpuMesh, CpuModel,
-    Cull, DirectionalLight, EuclideanSpace, FrameOutput, Gm, InnerSpace, InstancedMesh, Mat4, Mesh,
-    Model, Object, OrbitControl, PhysicalMaterial, Point3, Srgba, Vector3, Viewport, Window,
-    WindowSettings, degrees, rotation_matrix_from_dir_to_dir, vec3,
+    Axes, AxisAlignedBoundingBox, Camera, ClearState, ColorMaterial, Context, CpuMaterial, CpuMesh,
+    CpuTexture, Cull, EuclideanSpace, FrameInput, FrameInputGenerator, Geometry, Gm, InnerSpace,
+    InstancedMesh, Instances, Light, Mat4, Mesh, MouseButton, Object, PhysicalMaterial,
+    PhysicalPoint, Point3, SquareMatrix, Srgba, SurfaceSettings, TextureData, Vec3, Vector3,
+    Viewport, WindowedContext,
+    egui::{DragValue, Id, Ui, collapsing_header::CollapsingState},
+    rotation_matrix_from_dir_to_dir, vec3,
+};
+use winit::{
+    dpi::LogicalSize,
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::{Fullscreen, Window as WinitWindow, WindowBuilder},
 };
 
 const WINDOW_WIDTH: u32 = 1280;
 const WINDOW_HEIGHT: u32 = 720;
+const MIN_WINDOW_WIDTH: u32 = 640;
+const MIN_WINDOW_HEIGHT: u32 = 360;
+
+const TURNTABLE_FRAME_DELAY: Duration = Duration::from_millis(40);
+/// Most edits kept for undo.
+const HISTORY_LIMIT: usize = 200;
+/// Seconds the camera takes to move to a standard view or framing.
+const CAMERA_TRANSITION_SECONDS: f32 = 0.4;
+
+/// Viewer for tubes swept along curves and the models they belong to.
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Args {
+    /// Models, point files or OBJ curves to open on start
+    files: Vec<PathBuf>,
+
+    /// Load the files and report any errors without opening a window
+    #[arg(long)]
+    headless: bool,
+
+    /// With --headless, render the scene offscreen through EGL and write it to this PNG
+    #[arg(long, requires = "headless")]
+    output: Option<PathBuf>,
+
+    /// With --output, orbit the camera around its target and write this many numbered frames
+    #[arg(long, requires = "output", value_parser = clap::value_parser!(u32).range(1..))]
+    turntable: Option<u32>,
+
+    /// With --turntable, also write the frames as an animated GIF
+    #[arg(long, requires = "turntable")]
+    gif: bool,
+
+    #[arg(long, default_value = "Example")]
+    title: String,
+
+    /// Window or image width in logical pixels [default: the last window size, or 1280]
+    #[arg(long)]
+    width: Option<u32>,
+
+    /// Window or image height in logical pixels [default: the last window size, or 720]
+    #[arg(long)]
+    height: Option<u32>,
+
+    /// Start in borderless fullscreen, toggled with Ctrl+F
+    #[arg(long)]
+    fullscreen: bool,
+
+    /// Camera position as x,y,z [default: framing the loaded models, or 0,0,2]
+    #[arg(long, value_parser = parse_vec3, allow_hyphen_values = true)]
+    camera_position: Option<Vec3>,
+
+    /// Point the camera and orbit control look at, as x,y,z [default: the center of the
+    /// loaded models, or 0,0,0]
+    #[arg(long, value_parser = parse_vec3, allow_hyphen_values = true)]
+    camera_target: Option<Vec3>,
+
+    /// Show the tube on start, it is also shown once a path is loaded
+    #[arg(long)]
+    tube: bool,
+
+    #[arg(
+        long,
+        default_value_t = TubeParameters::default().tubular_segments,
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    tubular_segments: usize,
+
+    #[arg(
+        long,
+        default_value_t = TubeParameters::default().radial_segments,
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    radial_segments: usize,
+
+    /// Tube radius, replaces the mean of the radii read from point files
+    #[arg(long, value_parser = parse_radius)]
+    radius: Option<f32>,
+
+    /// Close the tube into a loop
+    #[arg(long)]
+    closed: bool,
+
+    /// Show the edges of the tube
+    #[arg(long)]
+    wireframe: bool,
+
+    /// Show the vertices of the tube
+    #[arg(long)]
+    vertices: bool,
+
+    /// Show the tangent, normal and binormal arrows along the tube
+    #[arg(long)]
+    arrows: bool,
+}
+
+impl Args {
+    /// Explicit sizes win over the remembered window size.
+    fn window_size(&self, settings: Option<ViewerSettings>) -> (u32, u32) {
+        let (width, height) = settings
+            .map(|settings| settings.window_size)
+            .unwrap_or((WINDOW_WIDTH, WINDOW_HEIGHT));
+
+        (self.width.unwrap_or(width), self.height.unwrap_or(height))
+    }
+
+    /// Models are framed from the start pose unless the camera was placed.
+    fn camera_pose(&self, models: &[LoadedModel], aspect: f32) -> CameraPose {
+        let pose = CameraPose {
+            position: self.camera_position.unwrap_or(vec3(0.0, 0.0, 2.0)),
+            target: self.camera_target.unwrap_or(vec3(0.0, 0.0, 0.0)),
+            up: vec3(0.0, 1.0, 0.0),
+        };
+
+        if self.camera_position.is_some() || self.camera_target.is_some() {
+            return pose;
+        }
+
+        frame_bounds(&pose, &models_bounds(models), aspect).unwrap_or(pose)
+    }
+
+    fn tube_parameters(&self) -> TubeParameters {
+        TubeParameters {
+            tubular_segments: self.tubular_segments,
+            radial_segments: self.radial_segments,
+            radius: self.radius.unwrap_or(TubeParameters::default().radius),
+            closed: self.closed,
+        }
+    }
+}
+
+fn parse_vec3(value: &str) -> Result<Vec3, String> {
+    let components = value
+        .split(',')
+        .map(|component| component.trim().parse::<f32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+
+    match components[..] {
+        [x, y, z] => Ok(vec3(x, y, z)),
+        _ => Err(format!("expected x,y,z, got {value:?}")),
+    }
+}
+
+fn parse_radius(value: &str) -> Result<f32, String> {
+    let radius = value.parse::<f32>().map_err(|error| error.to_string())?;
+    if radius > 0.0 && radius.is_finite() {
+        Ok(radius)
+    } else {
+        Err(format!("expected a positive radius, got {value:?}"))
+    }
+}
+
+/// Reads every file without a window, printing what was found, and renders
+/// the result when an output image is given.
+fn run_headless(args: &Args) -> ExitCode {
+    let mut failed = false;
+    let mut loaded = Vec::new();
+
+    for path in args.files.iter() {
+        let contents = read_file(path);
+
+        if let Some(points) = &contents.points {
+            println!("{:?}: {} points", path, points.points.len());
+        }
+
+        if !contents.curves.is_empty() {
+            println!("{:?}: {} curves", path, contents.curves.len());
+        }
+
+        if let Some(scene) = &contents.scene {
+            println!("{:?}: {} top level nodes", path, scene.children.len());
+        }
+
+        for error in contents.errors.iter() {
+            eprintln!("Unable to load {:?}: {}", path, error);
+            failed = true;
+        }
+
+        loaded.push((path.clone(), contents));
+    }
+
+    if let Some(output) = &args.output
+        && let Err(error) = render_headless(args, output, loaded)
+    {
+        eprintln!("Unable to render {:?}: {}", output, error);
+        failed = true;
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn model_node_ui(
+    ui: &mut Ui,
+    id: Id,
+    node: &mut ModelNode,
+    parts: &mut [ModelPart],
+    model_index: usize,
+    selected_part: &mut Option<(usize, usize)>,
+) {
+    CollapsingState::load_with_default_open(ui.ctx(), id, false)
+        .show_header(ui, |ui| ui.checkbox(&mut node.visible, node.name.as_str()))
+        .body(|ui| {
+            for &part_index in node.parts.iter() {
+                let part = &mut parts[part_index];
+                let is_selected = *selected_part == Some((model_index, part_index));
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut part.visible, "");
+
+                    if ui
+                        .selectable_label(is_selected, part.name.as_str())
+                        .clicked()
+                    {
+                        *selected_part = (!is_selected).then_some((model_index, part_index));
+                    }
+                });
+            }
+
+            for (child_index, child) in node.children.iter_mut().enumerate() {
+                model_node_ui(
+                    ui,
+                    id.with(child_index),
+                    child,
+                    parts,
+                    model_index,
+                    selected_part,
+                );
+            }
+        });
+}
+
+/// Renders the loaded files the way the viewer shows them on start, without a window.
+fn render_headless(
+    args: &Args,
+    output: &Path,
+    loaded: Vec<(PathBuf, FileContents)>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let context = OffscreenContext::new()?;
+    let (width, height) = args.window_size(None);
+
+    let mut models = Vec::new();
+    let mut curve: Box<dyn Curve> = Box::new(SineCurve);
+    let mut tube_parameters = args.tube_parameters();
+    let mut show_tube = args.tube;
+
+    for (path, contents) in loaded {
+        if let Some(point_list) = contents.points {
+            if let Some(radius) = point_list.mean_radius()
+                && args.radius.is_none()
+            {
+                tube_parameters.radius = radius;
+            }
+            curve = Box::new(point_list.to_catmull_rom(tube_parameters.closed));
+            show_tube = true;
+        }
+
+        if contents.curves.len() > 1 {
+            eprintln!(
+                "{:?}: only the first of {} curves is used",
+                path,
+                contents.curves.len()
+            );
+        }
+
+        if let Some(obj_curve) = contents.curves.into_iter().next() {
+            curve = obj_curve.curve;
+            show_tube = true;
+        }
+
+        if let Some(scene) = contents.scene {
+            models.push(LoadedModel::new(&context, &path, scene));
+        }
+    }
+
+    let mut objects: Vec<Box<dyn Object>> = Vec::new();
+
+    if show_tube {
+        let tube = Tube::from_parameters(curve.as_ref(), &tube_parameters);
+        let cpu_tube = tube.to_cpu_mesh();
+
+        objects.push(Box::new(Gm::new(
+            Mesh::new(&context, &cpu_tube),
+            PhysicalMaterial::default(),
+        )));
+
+        if args.wireframe {
+            objects.push(Box::new(Gm::new(
+                InstancedMesh::new(&context, &edge_transformations(&cpu_tube), &edge_mesh()),
+                wireframe_material(&context),
+            )));
+        }
+
+        if args.vertices {
+            objects.push(Box::new(Gm::new(
+                InstancedMesh::new(&context, &vertex_transformations(&cpu_tube), &vertex_mesh()),
+                wireframe_material(&context),
+            )));
+        }
+
+        if args.arrows {
+            let arrows = frame_arrows(
+                &context,
+                &tube.center_points,
+                &tube.frame,
+                &FrameArrowStyle::default(),
+            );
+            objects.extend(arrows.map(|arrow| Box::new(arrow) as Box<dyn Object>));
+        }
+    }
+
+    let scene_lights: Vec<SceneLight> = default_lights()
+        .iter()
+        .map(|light| SceneLight::new(&context, light))
+        .collect();
+    let active_lights: Vec<&dyn Light> = scene_lights.iter().map(SceneLight::light).collect();
+
+    let pose = args.camera_pose(&models, width as f32 / height as f32);
+    let camera = Camera::new_perspective(
+        Viewport::new_at_origo(width, height),
+        pose.position,
+        pose.target,
+        pose.up,
+        FIELD_OF_VIEW_Y,
+        Z_NEAR,
+        Z_FAR,
+    );
+
+    let render = |camera: &Camera| {
+        render_offscreen(
+            &context,
+            width,
+            height,
+            ClearState::color_and_depth(1., 1., 1., 1.0, 1.0),
+            |target| {
+                for loaded in models.iter() {
+                    loaded.for_each_visible_part(|part| {
+                        target.render(camera, &part.gm, &active_lights);
+                    });
+                }
+
+                target.render(
+                    camera,
+                    objects.iter().map(|object| object.as_ref()),
+                    &active_lights,
+                );
+            },
+        )
+    };
+
+    match args.turntable {
+        Some(frames) => export_turntable(
+            output,
+            frames as usize,
+            args.gif,
+            &camera,
+            pose.target,
+            render,
+        )?,
+        None => save_png(output, &render(&camera))?,
+    }
+
+    Ok(())
+}
+
+/// Writes a numbered PNG for every frame of an orbit around `target`, and
+/// optionally all of them as a GIF next to the first one.
+fn export_turntable(
+    path: &Path,
+    frames: usize,
+    gif: bool,
+    camera: &Camera,
+    target: Vec3,
+    render: impl Fn(&Camera) -> CpuTexture,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut export = TurntableExport::create(path, frames, gif, camera, target)?;
+
+    while let Some(camera) = export.next_camera() {
+        export.add_frame(&render(&camera))?;
+    }
+
+    Ok(())
+}
+
+/// A turntable being written one frame at a time, so the viewer can keep
+/// drawing in between.
+struct TurntableExport {
+    path: PathBuf,
+    frames: usize,
+    written: usize,
+    camera: Camera,
+    target: Vec3,
+    gif_writer: Option<GifWriter>,
+}
+
+impl TurntableExport {
+    fn create(
+        path: &Path,
+        frames: usize,
+        gif: bool,
+        camera: &Camera,
+        target: Vec3,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let gif_writer = if gif {
+            Some(GifWriter::create(
+                &path.with_extension("gif"),
+                camera.viewport().width,
+                camera.viewport().height,
+                TURNTABLE_FRAME_DELAY,
+            )?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            frames,
+            written: 0,
+            camera: camera.clone(),
+            target,
+            gif_writer,
+        })
+    }
+
+    /// The camera of the next frame to render, `None` once all are written.
+    fn next_camera(&self) -> Option<Camera> {
+        (self.written < self.frames)
+            .then(|| turntable_camera(&self.camera, self.target, self.written, self.frames))
+    }
+
+    fn add_frame(&mut self, image: &CpuTexture) -> Result<(), Box<dyn std::error::Error>> {
+        save_png(&sequence_path(&self.path, self.written), image)?;
+
+        if let Some(gif_writer) = self.gif_writer.as_mut() {
+            gif_writer.add_frame(image)?;
+        }
+
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Fraction of the frames written.
+    fn progress(&self) -> f32 {
+        self.written as f32 / self.frames.max(1) as f32
+    }
+}
+
+/// Bounds of the visible parts of every model, empty when there are none.
+fn models_bounds(models: &[LoadedModel]) -> AxisAlignedBoundingBox {
+    let mut bounds = AxisAlignedBoundingBox::EMPTY;
+
+    for loaded in models.iter() {
+        bounds.expand_with_aabb(loaded.bounds());
+    }
+
+    bounds
+}
+
+fn wireframe_material(context: &Context) -> PhysicalMaterial {
+    PhysicalMaterial::new_opaque(
+        context,
+        &CpuMaterial {
+            albedo: Srgba::new_opaque(220, 50, 50),
+            roughness: 0.7,
+            metallic: 0.8,
+            ..Default::default()
+        },
+    )
+}
+
+/// Three drag values on one labelled row.
+fn vec3_ui(ui: &mut Ui, label: &str, value: &mut Vec3, speed: f64) {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        ui.add(DragValue::new(&mut value.x).speed(speed).prefix("x "));
+        ui.add(DragValue::new(&mut value.y).speed(speed).prefix("y "));
+        ui.add(DragValue::new(&mut value.z).speed(speed).prefix("z "));
+    });
+}
+
+fn to_cpu_material(name: &str, material: &PhysicalMaterial) -> CpuMaterial {
+    CpuMaterial {
+        name: name.to_string(),
+        albedo: material.albedo,
+        metallic: material.metallic,
+        roughness: material.roughness,
+        ..Default::default()
+    }
+}
+
+/// The parts of the viewer state undo and redo can change.
+struct EditTarget<'a> {
+    models: &'a mut Vec<LoadedModel>,
+    spline: &'a mut Spline,
+    tube_parameters: &'a mut TubeParameters,
+}
+
+/// An undoable edit of the scene. Models out of the scene are kept in `model`,
+/// so bringing them back does not read the file again.
+enum SceneEdit {
+    AddModel {
+        index: usize,
+        model: Option<LoadedModel>,
+    },
+    RemoveModel {
+        index: usize,
+        model: Option<LoadedModel>,
+    },
+    Transform {
+        model: usize,
+        before: ModelTransform,
+        after: ModelTransform,
+    },
+    Curve {
+        before: Spline,
+        after: Spline,
+    },
+    TubeParameters {
+        before: TubeParameters,
+        after: TubeParameters,
+    },
+}
+
+impl SceneEdit {
+    fn take_model(target: &mut EditTarget, index: usize, model: &mut Option<LoadedModel>) {
+        *model = Some(target.models.remove(index));
+    }
 
-// sync model creation
-fn create_model(path: &Path, context: &Context) -> Gm<Mesh, PhysicalMaterial> {
-    let model_name = path.file_name().expect("No file name");
+    fn restore_model(target: &mut EditTarget, index: usize, model: &mut Option<LoadedModel>) {
+        if let Some(model) = model.take() {
+            target.models.insert(index, model);
+        }
+    }
+}
+
+impl Command<EditTarget<'_>> for SceneEdit {
+    fn undo(&mut self, target: &mut EditTarget) {
+        match self {
+            SceneEdit::AddModel { index, model } => Self::take_model(target, *index, model),
+            SceneEdit::RemoveModel { index, model } => Self::restore_model(target, *index, model),
+            SceneEdit::Transform { model, before, .. } => {
+                target.models[*model].set_transform(*before)
+            }
+            SceneEdit::Curve { before, .. } => *target.spline = before.clone(),
+            SceneEdit::TubeParameters { before, .. } => *target.tube_parameters = *before,
+        }
+    }
+
+    fn redo(&mut self, target: &mut EditTarget) {
+        match self {
+            SceneEdit::AddModel { index, model } => Self::restore_model(target, *index, model),
+            SceneEdit::RemoveModel { index, model } => Self::take_model(target, *index, model),
+            SceneEdit::Transform { model, after, .. } => {
+                target.models[*model].set_transform(*after)
+            }
+            SceneEdit::Curve { after, .. } => *target.spline = after.clone(),
+            SceneEdit::TubeParameters { after, .. } => *target.tube_parameters = *after,
+        }
+    }
+}
 
-    let mut load = three_d_asset::io::load(&[path]).expect(&format!("Unable to load {:?}", path));
+/// What the shadow maps were drawn from, they are drawn again when it changes.
+#[derive(Clone, Debug, PartialEq)]
+struct ShadowCasters {
+    /// Visible parts as model and part index, with where they are placed.
+    parts: Vec<(usize, usize, Mat4)>,
+    tube: bool,
+}
+
+impl ShadowCasters {
+    fn new(models: &[LoadedModel], tube: bool) -> Self {
+        let mut parts = Vec::new();
+
+        for (model_index, loaded) in models.iter().enumerate() {
+            loaded
+                .root
+                .for_each_visible_part(&loaded.parts, &mut |part_index| {
+                    parts.push((
+                        model_index,
+                        part_index,
+                        loaded.part_transformation(part_index),
+                    ))
+                });
+        }
 
-    let cpu_model: CpuModel = load
-        .deserialize(model_name)
-        .expect(&format!("Unable to deserialize {:?}", model_name));
+        Self { parts, tube }
+    }
+}
 
-    let model = Model::<PhysicalMaterial>::new(&context, &cpu_model)
-        .unwrap()
-        .remove(0)
-        .into();
+/// Which mesh the export button writes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ExportSource {
+    Tube,
+    SelectedPart,
+    /// The visible parts of the model the selected part belongs to.
+    SelectedModel,
+}
 
-    model
+impl ExportSource {
+    const ALL: [ExportSource; 3] = [
+        ExportSource::Tube,
+        ExportSource::SelectedPart,
+        ExportSource::SelectedModel,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            ExportSource::Tube => "Tube",
+            ExportSource::SelectedPart => "Selected part",
+            ExportSource::SelectedModel => "Selected model",
+        }
+    }
 }
 
 pub fn arrow_to_dir_pos(pos: Point3<f32>, dir: Vector3<f32>) -> three_d::Matrix4<f32> {
@@ -43,23 +680,35 @@ pub fn arrow_to_dir_pos(pos: Point3<f32>, dir: Vector3<f32>) -> three_d::Matrix4
         dir.normalize(),
     );
 
-    let brr = Mat4::from_translation(pos.to_vec())
-        * rotation
-        * Mat4::from_nonuniform_scale(0.6, 0.01, 0.01);
-    brr
+    Mat4::from_translation(pos.to_vec()) * rotation * Mat4::from_nonuniform_scale(0.6, 0.01, 0.01)
 }
 
-fn main() {
-    let application_title = String::from("Example");
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    if args.headless {
+        return run_headless(&args);
+    }
+
     let current_dir = std::env::current_dir().unwrap();
 
-    let window = Window::new(WindowSettings {
-        title: application_title,
-        min_size: (WINDOW_WIDTH, WINDOW_HEIGHT),
-        max_size: Some((WINDOW_WIDTH, WINDOW_HEIGHT)),
-        ..Default::default()
-    })
-    .unwrap();
+    let settings = load_settings();
+    let (width, height) = args.window_size(settings);
+    let mut fullscreen = args.fullscreen || settings.is_some_and(|settings| settings.fullscreen);
+
+    let event_loop = EventLoop::new();
+    let window = WindowBuilder::new()
+        .with_title(&args.title)
+        .with_min_inner_size(LogicalSize::new(MIN_WINDOW_WIDTH, MIN_WINDOW_HEIGHT))
+        .with_inner_size(LogicalSize::new(width, height))
+        .with_fullscreen(fullscreen.then_some(Fullscreen::Borderless(None)))
+        .build(&event_loop)
+        .unwrap();
+    window.focus_window();
+
+    let windowed_context =
+        WindowedContext::from_winit_window(&window, SurfaceSettings::default()).unwrap();
+    let mut frame_input_generator = FrameInputGenerator::from_winit_window(&window);
 
     // debug menu
     let mut show_axes = false;
@@ -70,31 +719,74 @@ fn main() {
     let mut show_debug_arrow = false;
 
     // tube debug
-    let mut show_tube = false;
-    let mut show_tube_indices = false;
-    let mut show_tube_vertices = false;
+    let mut show_tube = args.tube;
+    let mut show_tube_indices = args.wireframe;
+    let mut show_tube_vertices = args.vertices;
     let mut show_tube_transparent = false;
-    let mut show_tube_arrows = false;
-    let mut tubular_segments = 5;
-    let mut tubular_radial_segments = 5;
-    let mut tubular_radius = 0.2;
-    let mut tubular_closed = false;
+    let mut show_tube_arrows = args.arrows;
+    let mut tube_parameters = args.tube_parameters();
+
+    // export
+    let mut export_source = ExportSource::Tube;
+    let mut export_format = ExportFormat::Obj;
+
+    // screenshots, taken after the next frame is drawn
+    let mut screenshot_requested = false;
+    let mut screenshot_include_gui = false;
+    let mut screenshot_scale: u32 = 1;
+
+    // turntable frames, one written after each drawn frame
+    let mut turntable_request: Option<PathBuf> = None;
+    let mut turntable_export: Option<TurntableExport> = None;
+    let mut turntable_frames: usize = 36;
+    let mut turntable_gif = false;
+
+    // projects are opened after the panel is drawn, their models and curve
+    // file through the loader
+    let mut project_request: Option<PathBuf> = None;
+    let mut pending_models: Vec<ProjectModel> = Vec::new();
+    let mut pending_curve: Option<PathBuf> = None;
 
     // gl context init
-    let context = window.gl();
+    let context: Context = windowed_context.clone();
     let mut gui = three_d::GUI::new(&context);
 
     let axes = Gm::new(Axes::new(&context, 0.01, 10.0), ColorMaterial::default());
 
-    let mut models = Vec::new();
+    let mut models: Vec<LoadedModel> = Vec::new();
+    let mut selected_part: Option<(usize, usize)> = None;
 
-    let ambient = AmbientLight::new(&context, 0.4, Srgba::WHITE);
-    let directional = DirectionalLight::new(
-        &context,
-        10.0,
-        Srgba::new_opaque(204, 178, 127),
-        vec3(0.0, -1.0, -1.0),
-    );
+    // background file loading, `None` marks the end of a batch
+    let (load_sender, load_receiver) = mpsc::channel::<Option<(PathBuf, FileContents)>>();
+    let mut loading = !args.files.is_empty();
+
+    // files from the command line are read like picked ones, in order
+    if loading {
+        let sender = load_sender.clone();
+        let files = args.files.clone();
+
+        std::thread::spawn(move || {
+            for buf in files {
+                let contents = read_file(&buf);
+                let _ = sender.send(Some((buf, contents)));
+            }
+
+            let _ = sender.send(None);
+        });
+    }
+
+    // errors shown in a window until dismissed
+    let mut error_messages: Vec<String> = Vec::new();
+
+    // edited in the panel, the lights to render with are rebuilt when they change
+    let mut lights = default_lights();
+    let mut built_lights: Vec<LightSettings> = Vec::new();
+    let mut scene_lights: Vec<SceneLight> = Vec::new();
+    let mut new_light_type = LightType::Point;
+
+    // shadow maps are drawn again only when their lights or casters change
+    let mut shadow_casters: Option<ShadowCasters> = None;
+    let mut shadows_stale = true;
 
     // debug arrow
     let arrow = CpuMesh::arrow(0.9, 0.5, 16);
@@ -117,23 +809,51 @@ fn main() {
     );
 
     // tube
-    let curve = SineCurve;
-
-    let tube = Tube::new(
-        &curve,
-        tubular_segments,
-        tubular_closed,
-        tubular_radius,
-        tubular_radial_segments,
+    let mut curve: Box<dyn Curve> = Box::new(SineCurve);
+    // what `curve` was made from, for saving projects
+    let mut curve_source = ProjectCurve::Default;
+
+    let tube = Tube::from_parameters(curve.as_ref(), &tube_parameters);
+
+    // what the gpu resources below were last built from, `None` forces a rebuild
+    let mut built_tube_parameters = Some(tube_parameters);
+    let mut built_tube_transparent = false;
+    // the wireframe overlays are only rebuilt once they are shown
+    let mut tube_edges_stale = false;
+    let mut tube_vertices_stale = false;
+
+    // camera riding along the curve, following it as it is rebuilt
+    let mut riding = false;
+    let mut fly_through = FlyThrough::new(curve.as_ref(), tube_parameters.closed);
+
+    let mut cpu_tube = tube.to_cpu_mesh();
+
+    // control points, replacing the curve while it is edited in the viewport
+    let mut editing_curve = false;
+    let mut spline = Spline::from_curve(curve.as_ref(), 8, SplineType::CatmullRom);
+    let mut built_spline: Option<(Spline, bool)> = None;
+    let mut selected_control_point: Option<usize> = None;
+
+    let mut control_point_sphere = CpuMesh::sphere(12);
+    control_point_sphere
+        .transform(Mat4::from_scale(0.035))
+        .unwrap();
+    let control_point_material = PhysicalMaterial::new_opaque(
+        &context,
+        &CpuMaterial {
+            albedo: Srgba::WHITE,
+            roughness: 0.7,
+            ..Default::default()
+        },
+    );
+    let mut control_points = Gm::new(
+        InstancedMesh::new(&context, &Instances::default(), &control_point_sphere),
+        control_point_material.clone(),
+    );
+    let mut control_polygon = Gm::new(
+        InstancedMesh::new(&context, &Instances::default(), &edge_mesh()),
+        control_point_material,
     );
-
-    let mut cpu_tube = CpuMesh {
-        positions: three_d::Positions::F32(tube.vertices),
-        indices: three_d::Indices::U32(tube.indices),
-        ..Default::default()
-    };
-
-    cpu_tube.compute_normals();
 
     let default_material = PhysicalMaterial::default();
     let transparent_material = PhysicalMaterial::new_transparent(
@@ -150,124 +870,100 @@ fn main() {
     );
 
     let mut gm_tube = Gm::new(Mesh::new(&context, &cpu_tube), default_material.clone());
+    let mut tube_bvh = Bvh::new(&cpu_tube);
 
     // tube arrows
 
-    let mut tube_arrows = vec![];
-
-    let arrow_scale = Mat4::from_nonuniform_scale(0.3, 0.7, 0.7);
-
-    // TODO replace tangent/binormal/normal arrow generation with method, or function
-    for (curve_index, point) in tube.center_points.iter().enumerate() {
-        let mut arrow = CpuMesh::arrow(0.9, 0.5, 16);
-        arrow.transform(arrow_scale).unwrap();
-
-        let rotation = arrow_to_dir_pos(Point3::origin(), tube.tangents_frame[curve_index]);
-        arrow.transform(rotation).unwrap();
-
-        arrow.transform(Mat4::from_translation(*point)).unwrap();
-
-        tube_arrows.push(Gm::new(
-            Mesh::new(&context, &arrow),
-            PhysicalMaterial {
-                albedo: Srgba {
-                    r: 255,
-                    g: 255,
-                    b: 0,
-                    a: 0,
-                },
-                ..Default::default()
-            },
-        ));
-    }
-
-    for (curve_index, point) in tube.center_points.iter().enumerate() {
-        let mut arrow = CpuMesh::arrow(0.9, 0.5, 16);
-        arrow.transform(arrow_scale).unwrap();
-
-        let rotation = arrow_to_dir_pos(Point3::origin(), tube.normals_frame[curve_index]);
-        arrow.transform(rotation).unwrap();
-
-        arrow.transform(Mat4::from_translation(*point)).unwrap();
-
-        tube_arrows.push(Gm::new(
-            Mesh::new(&context, &arrow),
-            PhysicalMaterial {
-                albedo: Srgba {
-                    r: 190,
-                    g: 100,
-                    b: 0,
-                    a: 0,
-                },
-                ..Default::default()
-            },
-        ));
-    }
-
-    for (curve_index, point) in tube.center_points.iter().enumerate() {
-        let mut arrow = CpuMesh::arrow(0.9, 0.5, 16);
-        arrow.transform(arrow_scale).unwrap();
+    let arrow_style = FrameArrowStyle::default();
+    let mut tube_arrows = frame_arrows(&context, &tube.center_points, &tube.frame, &arrow_style);
 
-        let rotation = arrow_to_dir_pos(Point3::origin(), tube.binormals_frame[curve_index]);
-        arrow.transform(rotation).unwrap();
+    // tube wireframe
+    let wireframe_material = wireframe_material(&context);
+    let cylinder = edge_mesh();
 
-        arrow.transform(Mat4::from_translation(*point)).unwrap();
+    let mut edges = Gm::new(
+        InstancedMesh::new(&context, &edge_transformations(&cpu_tube), &cylinder),
+        wireframe_material.clone(),
+    );
 
-        tube_arrows.push(Gm::new(
-            Mesh::new(&context, &arrow),
-            PhysicalMaterial {
-                albedo: Srgba::GREEN,
-                ..Default::default()
-            },
-        ));
-    }
+    let sphere = vertex_mesh();
+    let mut vertices = Gm::new(
+        InstancedMesh::new(&context, &vertex_transformations(&cpu_tube), &sphere),
+        wireframe_material.clone(),
+    );
 
-    // tube wireframe
-    let wireframe_material = PhysicalMaterial::new_opaque(
+    // selected model part outline
+    let selection_material = PhysicalMaterial::new_opaque(
         &context,
         &CpuMaterial {
-            albedo: Srgba::new_opaque(220, 50, 50),
+            albedo: Srgba::new_opaque(255, 140, 0),
             roughness: 0.7,
-            metallic: 0.8,
             ..Default::default()
         },
     );
 
-    let mut cylinder = CpuMesh::cylinder(12);
-    cylinder
-        .transform(Mat4::from_nonuniform_scale(1.0, 0.007, 0.007))
-        .unwrap();
+    let mut highlighted_part: Option<(usize, usize)> = None;
+    let mut selection_edges: Option<Gm<InstancedMesh, PhysicalMaterial>> = None;
 
-    let mut edges = Gm::new(
-        InstancedMesh::new(&context, &edge_transformations(&cpu_tube), &cylinder),
-        wireframe_material.clone(),
+    // clicked triangle, with its nearest edge and vertex
+    let pick_material = PhysicalMaterial::new_opaque(
+        &context,
+        &CpuMaterial {
+            albedo: Srgba::new_opaque(200, 0, 200),
+            roughness: 0.7,
+            ..Default::default()
+        },
     );
-
-    let mut sphere = CpuMesh::sphere(8);
-    sphere.transform(Mat4::from_scale(0.015)).unwrap();
-    let mut vertices = Gm::new(
-        InstancedMesh::new(&context, &vertex_transformations(&cpu_tube), &sphere),
-        wireframe_material.clone(),
+    let nearest_material = PhysicalMaterial::new_opaque(
+        &context,
+        &CpuMaterial {
+            albedo: Srgba::new_opaque(0, 170, 255),
+            roughness: 0.7,
+            ..Default::default()
+        },
     );
 
+    let mut press_position: Option<PhysicalPoint> = None;
+    let mut picked: Option<PickHit> = None;
+    let mut highlighted_pick: Option<PickHit> = None;
+    let mut pick_highlight: Vec<Gm<InstancedMesh, PhysicalMaterial>> = Vec::new();
+
     // camera part
-    let camera_position = vec3(0., 0., 2.);
-    let target = vec3(0., 0.0, 0.);
-    let up_v = vec3(0., 1., 0.);
+    let start_pose = args.camera_pose(&[], 1.0);
 
     let mut camera = Camera::new_perspective(
-        window.viewport(),
-        camera_position,
-        target,
-        up_v,
-        degrees(45.0),
-        0.1,
-        100.0,
+        Viewport::new_at_origo(window.inner_size().width, window.inner_size().height),
+        start_pose.position,
+        start_pose.target,
+        start_pose.up,
+        FIELD_OF_VIEW_Y,
+        Z_NEAR,
+        Z_FAR,
     );
 
-    let mut control = OrbitControl::new(camera.target(), 1.0, 100.0);
-
-    window.render_loop(move |mut frame_input| {
+    let mut control = Navigation::new(camera.target(), 1.0, 100.0);
+    let mut projection = Projection::Perspective;
+
+    // standard views and framing animate the camera, the models from the
+    // command line are framed once loaded
+    let mut camera_request: Option<CameraPose> = None;
+    let mut camera_transition: Option<CameraTransition> = None;
+    let mut frame_loaded_models = !args.files.is_empty();
+
+    // edits the selected model, or the debug sphere when nothing is selected
+    let mut gizmo = Gizmo::new(&context);
+
+    // edits are compared against what was last recorded once the pointer is
+    // released, so a whole drag becomes one step
+    let mut history: History<SceneEdit> = History::new(HISTORY_LIMIT);
+    let mut committed_transforms: Vec<ModelTransform> = Vec::new();
+    let mut committed_spline = spline.clone();
+    let mut committed_tube_parameters = tube_parameters;
+    let mut undo_requested = false;
+    let mut redo_requested = false;
+    let mut pointer_down = false;
+
+    let mut render_frame = move |mut frame_input: FrameInput, window: &WinitWindow| {
         let mut panel_width = 0.0;
 
         gui.update(
@@ -278,39 +974,292 @@ fn main() {
             |gui_context| {
                 use three_d::egui::*;
 
-                SidePanel::left("side_panel").show(gui_context, |ui| {
-                    ui.heading("Debug Panel");
+                if gui_context.input_mut(|input| {
+                    input.consume_shortcut(&KeyboardShortcut::new(Modifiers::COMMAND, Key::P))
+                }) {
+                    screenshot_requested = true;
+                }
 
-                    ui.checkbox(&mut show_axes, "Display axes");
-                    ui.checkbox(&mut show_debug_arrow, "Display debug arrow");
-                    ui.checkbox(&mut show_debug_sphere, "Display debug sphere");
-                    ui.checkbox(&mut show_tube, "Display tube");
+                if gui_context.input_mut(|input| {
+                    input.consume_shortcut(&KeyboardShortcut::new(Modifiers::COMMAND, Key::F))
+                }) {
+                    fullscreen = !fullscreen;
+                }
 
-                    if show_tube {
-                        ui.checkbox(&mut show_tube_vertices, "Display tube vertices");
-                        ui.checkbox(&mut show_tube_indices, "Display tube indices");
-                        ui.checkbox(&mut show_tube_transparent, "Display tube as transparent");
-                        ui.checkbox(&mut show_tube_arrows, "Display tube vector arrows");
-                        ui.checkbox(&mut tubular_closed, "Tubular closed");
+                // text fields have their own undo
+                if !gui_context.wants_keyboard_input() {
+                    let redo = KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z);
+                    let undo = KeyboardShortcut::new(Modifiers::COMMAND, Key::Z);
 
-                        ui.add(
-                            Slider::new(&mut tubular_segments, 1..=100).text("Tubular segments"),
-                        );
-                        ui.add(
-                            Slider::new(&mut tubular_radial_segments, 1..=100)
-                                .text("Tubular radial segments"),
-                        );
-                        ui.add(Slider::new(&mut tubular_radius, 0.1..=1.0).text("Tubular radius"));
+                    // redo first, undo also matches with shift held
+                    if gui_context.input_mut(|input| input.consume_shortcut(&redo)) {
+                        redo_requested = true;
+                    } else if gui_context.input_mut(|input| input.consume_shortcut(&undo)) {
+                        undo_requested = true;
                     }
+                }
 
-                    if show_debug_sphere {
-                        ui.add(
-                            Slider::new(&mut sphere_position.x, -1.0..=1.0)
-                                .text("Sphere X position"),
-                        );
-                        ui.add(
-                            Slider::new(&mut sphere_position.y, -1.0..=1.0)
-                                .text("Sphere Y position"),
+                pointer_down = gui_context.input(|input| input.pointer.any_down());
+
+                if editing_curve
+                    && !gui_context.wants_keyboard_input()
+                    && let Some(index) = selected_control_point
+                    && gui_context
+                        .input_mut(|input| input.consume_key(Modifiers::NONE, Key::Delete))
+                    && spline.remove(index)
+                {
+                    selected_control_point = None;
+                }
+
+                let side_panel = SidePanel::left("side_panel").show(gui_context, |ui| {
+                    ui.heading("Debug Panel");
+
+                    ui.checkbox(&mut fullscreen, "Fullscreen")
+                        .on_hover_text("Ctrl+F");
+
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(history.can_undo(), Button::new("Undo"))
+                            .on_hover_text("Ctrl+Z")
+                            .clicked()
+                        {
+                            undo_requested = true;
+                        }
+
+                        if ui
+                            .add_enabled(history.can_redo(), Button::new("Redo"))
+                            .on_hover_text("Ctrl+Shift+Z")
+                            .clicked()
+                        {
+                            redo_requested = true;
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        for mode in Projection::ALL {
+                            if ui.radio_value(&mut projection, mode, mode.name()).changed() {
+                                projection.apply(&mut camera);
+                            }
+                        }
+                    });
+
+                    ComboBox::from_label("Navigation")
+                        .selected_text(control.mode.name())
+                        .show_ui(ui, |ui| {
+                            for mode in NavigationMode::ALL {
+                                ui.selectable_value(&mut control.mode, mode, mode.name())
+                                    .on_hover_text(mode.help());
+                            }
+                        })
+                        .response
+                        .on_hover_text(control.mode.help());
+
+                    ui.add(Slider::new(&mut control.rotate_speed, 0.1..=5.0).text("Rotate speed"));
+
+                    if control.mode == NavigationMode::Fly {
+                        ui.add(
+                            Slider::new(&mut control.fly_speed, 0.1..=20.0)
+                                .logarithmic(true)
+                                .text("Fly speed"),
+                        );
+                    } else {
+                        ui.add(Slider::new(&mut control.zoom_speed, 0.1..=5.0).text("Zoom speed"));
+                        ui.checkbox(&mut control.zoom_to_cursor, "Zoom to cursor");
+                    }
+
+                    // views are relative to where a running animation ends up
+                    let current_pose = camera_transition.map_or_else(
+                        || CameraPose::of(&camera, control.target),
+                        |transition| transition.target(),
+                    );
+                    let aspect = camera.viewport().aspect();
+
+                    ui.horizontal(|ui| {
+                        for view in StandardView::ALL {
+                            if ui.button(view.name()).clicked() {
+                                camera_request = Some(view.pose(&current_pose));
+                            }
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        let selected_bounds = match (selected_control_point, selected_part) {
+                            (Some(index), _) if editing_curve => {
+                                let point = spline.points[index];
+                                Some(AxisAlignedBoundingBox::new_with_positions(&[point]))
+                            }
+                            (_, Some((model, part))) => Some(models[model].part_bounds(part)),
+                            _ if picked.is_some_and(|hit| hit.target == PickTarget::Tube) => {
+                                Some(tube_bvh.bounds())
+                            }
+                            _ => None,
+                        };
+
+                        if ui
+                            .add_enabled(selected_bounds.is_some(), Button::new("Frame selected"))
+                            .clicked()
+                            && let Some(bounds) = selected_bounds
+                        {
+                            camera_request = frame_bounds(&current_pose, &bounds, aspect);
+                        }
+
+                        if ui.button("Frame all").clicked() {
+                            let mut bounds = models_bounds(&models);
+
+                            if show_tube {
+                                bounds.expand_with_aabb(tube_bvh.bounds());
+                            }
+
+                            if editing_curve {
+                                bounds.expand(&spline.points);
+                            }
+
+                            camera_request = frame_bounds(&current_pose, &bounds, aspect);
+                        }
+                    });
+
+                    if ui
+                        .checkbox(&mut riding, "Ride along curve")
+                        .on_hover_text("Move the camera through the tube")
+                        .changed()
+                        && riding
+                    {
+                        fly_through.set_curve(curve.as_ref(), tube_parameters.closed);
+                        camera_transition = None;
+                        show_tube = true;
+                    }
+
+                    if riding {
+                        ui.horizontal(|ui| {
+                            let label = if fly_through.playing { "Pause" } else { "Play" };
+
+                            if ui.button(label).clicked() {
+                                // playing again from the end starts over
+                                if !fly_through.playing && fly_through.u >= 1.0 {
+                                    fly_through.u = 0.0;
+                                }
+                                fly_through.playing = !fly_through.playing;
+                            }
+
+                            if ui.button("Restart").clicked() {
+                                fly_through.u = 0.0;
+                            }
+
+                            ui.checkbox(&mut fly_through.looping, "Loop");
+                        });
+
+                        ui.add(Slider::new(&mut fly_through.u, 0.0..=1.0).text("Ride position"));
+                        ui.add(
+                            Slider::new(&mut fly_through.speed, 0.05..=5.0)
+                                .logarithmic(true)
+                                .text("Ride speed"),
+                        );
+                    }
+
+                    ui.checkbox(&mut show_axes, "Display axes");
+                    ui.checkbox(&mut show_debug_arrow, "Display debug arrow");
+                    ui.checkbox(&mut show_debug_sphere, "Display debug sphere");
+
+                    ui.horizontal(|ui| {
+                        for mode in GizmoMode::ALL {
+                            // points and the sphere only have a position
+                            let enabled = !gizmo.translate_only || mode == GizmoMode::Translate;
+                            ui.add_enabled_ui(enabled, |ui| {
+                                ui.radio_value(&mut gizmo.mode, mode, mode.name());
+                            });
+                        }
+                    });
+                    ui.checkbox(&mut gizmo.snapping, "Snap gizmo")
+                        .on_hover_text("Hold Ctrl while dragging to toggle");
+                    ui.checkbox(&mut show_tube, "Display tube");
+
+                    if show_tube {
+                        ui.checkbox(&mut show_tube_vertices, "Display tube vertices");
+                        ui.checkbox(&mut show_tube_indices, "Display tube indices");
+                        ui.checkbox(&mut show_tube_transparent, "Display tube as transparent");
+                        ui.checkbox(&mut show_tube_arrows, "Display tube vector arrows");
+                        ui.checkbox(&mut tube_parameters.closed, "Tubular closed");
+
+                        ui.add(
+                            Slider::new(&mut tube_parameters.tubular_segments, 1..=100)
+                                .text("Tubular segments"),
+                        );
+                        ui.add(
+                            Slider::new(&mut tube_parameters.radial_segments, 1..=100)
+                                .text("Tubular radial segments"),
+                        );
+                        ui.add(
+                            Slider::new(&mut tube_parameters.radius, 0.1..=1.0)
+                                .text("Tubular radius"),
+                        );
+                    }
+
+                    if ui
+                        .checkbox(&mut editing_curve, "Edit curve")
+                        .on_hover_text(
+                            "Click a control point to move it with the gizmo, \
+                             Shift+click to add one after it",
+                        )
+                        .changed()
+                        && editing_curve
+                    {
+                        spline = Spline::from_curve(curve.as_ref(), 8, spline.spline_type);
+                        committed_spline = spline.clone();
+                        selected_control_point = None;
+                        show_tube = true;
+                    }
+
+                    if editing_curve {
+                        ComboBox::from_label("Curve type")
+                            .selected_text(spline.spline_type.name())
+                            .show_ui(ui, |ui| {
+                                for spline_type in SplineType::ALL {
+                                    ui.selectable_value(
+                                        &mut spline.spline_type,
+                                        spline_type,
+                                        spline_type.name(),
+                                    );
+                                }
+                            });
+
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{} control points", spline.points.len()));
+
+                            if ui.button("Insert").clicked() {
+                                selected_control_point =
+                                    Some(match spline.insertion_point(selected_control_point) {
+                                        Some(after) => spline.insert_after(after),
+                                        None => spline.push(control.target),
+                                    });
+                            }
+
+                            if ui
+                                .add_enabled(
+                                    selected_control_point.is_some(),
+                                    Button::new("Delete"),
+                                )
+                                .on_hover_text("Delete")
+                                .clicked()
+                                && let Some(index) = selected_control_point
+                                && spline.remove(index)
+                            {
+                                selected_control_point = None;
+                            }
+                        });
+
+                        if let Some(index) = selected_control_point {
+                            vec3_ui(ui, "Point", &mut spline.points[index], 0.01);
+                        }
+                    }
+
+                    if show_debug_sphere {
+                        ui.add(
+                            Slider::new(&mut sphere_position.x, -1.0..=1.0)
+                                .text("Sphere X position"),
+                        );
+                        ui.add(
+                            Slider::new(&mut sphere_position.y, -1.0..=1.0)
+                                .text("Sphere Y position"),
                         );
                         ui.add(
                             Slider::new(&mut sphere_position.z, -1.0..=1.0)
@@ -318,26 +1267,634 @@ fn main() {
                         );
                     }
 
-                    if ui.button("Select file").clicked() {
+                    if loading {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Loading...");
+                        });
+                    } else if ui.button("Select file").clicked() {
+                        loading = true;
+
+                        let sender = load_sender.clone();
+                        let current_dir = current_dir.clone();
+
+                        // pick and parse off the draw thread, gpu upload happens once it is done
+                        std::thread::spawn(move || {
+                            let response = rfd::FileDialog::new()
+                                .set_directory(&current_dir)
+                                .pick_file();
+
+                            // the viewer may have been closed in the meantime
+                            if let Some(buf) = response {
+                                let contents = read_file(&buf);
+                                let _ = sender.send(Some((buf, contents)));
+                            }
+
+                            let _ = sender.send(None);
+                        });
+                    }
+
+                    ComboBox::from_label("Export mesh")
+                        .selected_text(export_source.name())
+                        .show_ui(ui, |ui| {
+                            for source in ExportSource::ALL {
+                                ui.selectable_value(&mut export_source, source, source.name());
+                            }
+                        });
+
+                    ComboBox::from_label("Export format")
+                        .selected_text(export_format.name())
+                        .show_ui(ui, |ui| {
+                            for format in ExportFormat::ALL {
+                                ui.selectable_value(&mut export_format, format, format.name());
+                            }
+                        });
+
+                    let can_export = export_source == ExportSource::Tube || selected_part.is_some();
+
+                    if ui
+                        .add_enabled(can_export, Button::new("Export"))
+                        .on_disabled_hover_text("Select a model part first")
+                        .clicked()
+                    {
+                        // the mesh in the scene, its material and a file name for it
+                        let (mesh, material, name) = match (export_source, selected_part) {
+                            (ExportSource::SelectedPart, Some((model, part))) => {
+                                let loaded = &models[model];
+                                (
+                                    loaded.part_mesh(part),
+                                    loaded.parts[part].cpu_material.clone(),
+                                    loaded.parts[part].name.clone(),
+                                )
+                            }
+                            (ExportSource::SelectedModel, Some((model, part))) => {
+                                let loaded = &models[model];
+                                let name = Path::new(loaded.name())
+                                    .file_stem()
+                                    .map_or("model".into(), |stem| stem.to_string_lossy().into());
+                                (loaded.mesh(), loaded.parts[part].cpu_material.clone(), name)
+                            }
+                            _ => (
+                                cpu_tube.clone(),
+                                to_cpu_material("tube", &gm_tube.material),
+                                "tube".to_string(),
+                            ),
+                        };
+
+                        // block draw thread
+                        let response = rfd::FileDialog::new()
+                            .set_directory(&current_dir)
+                            .add_filter(export_format.name(), &[export_format.extension()])
+                            .set_file_name(format!("{}.{}", name, export_format.extension()))
+                            .save_file();
+
+                        if let Some(buf) = response
+                            && let Err(error) = export_mesh(&buf, &mesh, &material, export_format)
+                        {
+                            error_messages.push(format!("Unable to export {:?}: {}", buf, error));
+                        }
+                    }
+
+                    if ui.button("Export scene").clicked() {
                         // block draw thread
                         let response = rfd::FileDialog::new()
                             .set_directory(&current_dir)
-                            .pick_file();
+                            .add_filter("glTF binary", &["glb"])
+                            .set_file_name("scene.glb")
+                            .save_file();
+
+                        if let Some(buf) = response {
+                            let tube_cpu_material = to_cpu_material("tube", &gm_tube.material);
+
+                            let mut nodes = Vec::new();
+
+                            for loaded in models.iter() {
+                                let model_matrix = loaded.transform().matrix();
+
+                                loaded.for_each_visible_part(|part| {
+                                    nodes.push(GlbNode {
+                                        name: &part.name,
+                                        mesh: &part.cpu_mesh,
+                                        material: &part.cpu_material,
+                                        transformation: model_matrix * part.transformation,
+                                    });
+                                });
+                            }
 
-                        match response {
-                            Some(buf) => {
-                                let new_model = create_model(buf.as_path(), &context);
-                                models.push(new_model);
+                            if show_tube {
+                                nodes.push(GlbNode {
+                                    name: "tube",
+                                    mesh: &cpu_tube,
+                                    material: &tube_cpu_material,
+                                    transformation: Mat4::identity(),
+                                });
                             }
-                            None => {}
+
+                            if let Err(error) = export_glb(&buf, &nodes) {
+                                error_messages
+                                    .push(format!("Unable to export {:?}: {}", buf, error));
+                            }
+                        }
+                    }
+
+                    ui.separator();
+
+                    if ui
+                        .button("Save screenshot")
+                        .on_hover_text("Ctrl+P, saved to the working directory")
+                        .clicked()
+                    {
+                        screenshot_requested = true;
+                    }
+
+                    ui.checkbox(&mut screenshot_include_gui, "Include panel");
+                    ui.add_enabled(
+                        !screenshot_include_gui,
+                        Slider::new(&mut screenshot_scale, 1..=4).text("Screenshot scale"),
+                    );
+
+                    ui.add(Slider::new(&mut turntable_frames, 4..=360).text("Turntable frames"));
+                    ui.checkbox(&mut turntable_gif, "Turntable GIF");
+
+                    if let Some(export) = &turntable_export {
+                        let mut cancel = false;
+
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                ProgressBar::new(export.progress())
+                                    .desired_width(160.0)
+                                    .text(format!("{}/{}", export.written, export.frames)),
+                            );
+                            cancel = ui.button("Cancel").clicked();
+                        });
+
+                        if cancel {
+                            turntable_export = None;
+                        }
+                    } else if ui.button("Export turntable").clicked() {
+                        // block draw thread
+                        turntable_request = rfd::FileDialog::new()
+                            .set_directory(&current_dir)
+                            .add_filter("PNG", &["png"])
+                            .set_file_name("turntable.png")
+                            .save_file();
+                    }
+
+                    ui.separator();
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Save project").clicked() {
+                            // block draw thread
+                            let response = rfd::FileDialog::new()
+                                .set_directory(&current_dir)
+                                .add_filter("Project", &["json"])
+                                .set_file_name("project.json")
+                                .save_file();
+
+                            if let Some(buf) = response {
+                                let project = Project {
+                                    models: models
+                                        .iter()
+                                        .map(|loaded| ProjectModel {
+                                            path: loaded.path().to_path_buf(),
+                                            name: loaded.name().to_string(),
+                                            visible: loaded.root.visible,
+                                            transform: loaded.transform(),
+                                        })
+                                        .collect(),
+                                    curve: curve_source.clone(),
+                                    tube_parameters,
+                                    camera: CameraPose::of(&camera, control.target),
+                                    projection,
+                                    lights: lights.clone(),
+                                    display: DisplayOptions {
+                                        axes: show_axes,
+                                        debug_arrow: show_debug_arrow,
+                                        debug_sphere: show_debug_sphere,
+                                        sphere_position,
+                                        tube: show_tube,
+                                        tube_vertices: show_tube_vertices,
+                                        tube_edges: show_tube_indices,
+                                        tube_transparent: show_tube_transparent,
+                                        tube_arrows: show_tube_arrows,
+                                    },
+                                };
+
+                                if let Err(error) = save_project(&buf, &project) {
+                                    error_messages
+                                        .push(format!("Unable to save {:?}: {}", buf, error));
+                                }
+                            }
+                        }
+
+                        if ui
+                            .add_enabled(!loading, Button::new("Open project"))
+                            .clicked()
+                        {
+                            // block draw thread
+                            project_request = rfd::FileDialog::new()
+                                .set_directory(&current_dir)
+                                .add_filter("Project", &["json"])
+                                .pick_file();
+                        }
+                    });
+
+                    ui.separator();
+
+                    CollapsingHeader::new("Lights").show(ui, |ui| {
+                        let mut removed_light = None;
+
+                        for (index, light) in lights.iter_mut().enumerate() {
+                            ui.horizontal(|ui| {
+                                ComboBox::from_id_salt(("light", index))
+                                    .selected_text(light.light_type.name())
+                                    .show_ui(ui, |ui| {
+                                        for light_type in LightType::ALL {
+                                            ui.selectable_value(
+                                                &mut light.light_type,
+                                                light_type,
+                                                light_type.name(),
+                                            );
+                                        }
+                                    });
+
+                                let mut color = [light.color.r, light.color.g, light.color.b];
+                                if ui.color_edit_button_srgb(&mut color).changed() {
+                                    light.color = Srgba::new_opaque(color[0], color[1], color[2]);
+                                }
+
+                                if ui.small_button("Remove").clicked() {
+                                    removed_light = Some(index);
+                                }
+                            });
+
+                            ui.add(Slider::new(&mut light.intensity, 0.0..=20.0).text("Intensity"));
+
+                            if light.light_type.has_position() {
+                                vec3_ui(ui, "Position", &mut light.position, 0.01);
+                            }
+
+                            if light.light_type.has_direction() {
+                                vec3_ui(ui, "Direction", &mut light.direction, 0.01);
+                            }
+
+                            if light.light_type == LightType::Spot {
+                                ui.add(Slider::new(&mut light.cutoff, 1.0..=90.0).text("Cutoff"));
+                            }
+
+                            if light.light_type.has_shadows() {
+                                ui.checkbox(&mut light.shadows, "Shadows");
+                            }
+
+                            ui.separator();
+                        }
+
+                        if let Some(index) = removed_light {
+                            lights.remove(index);
+                        }
+
+                        ui.horizontal(|ui| {
+                            ComboBox::from_id_salt("new_light")
+                                .selected_text(new_light_type.name())
+                                .show_ui(ui, |ui| {
+                                    for light_type in LightType::ALL {
+                                        ui.selectable_value(
+                                            &mut new_light_type,
+                                            light_type,
+                                            light_type.name(),
+                                        );
+                                    }
+                                });
+
+                            if ui.button("Add light").clicked() {
+                                lights.push(LightSettings::new(new_light_type));
+                            }
+                        });
+                    });
+
+                    if let Some(hit) = &picked {
+                        ui.separator();
+
+                        let name = match hit.target {
+                            PickTarget::Part { model, part } => {
+                                format!(
+                                    "{} / {}",
+                                    models[model].name(),
+                                    models[model].parts[part].name
+                                )
+                            }
+                            PickTarget::Tube => "Tube".to_string(),
+                        };
+                        let (a, b) = hit.nearest_edge();
+
+                        ui.label(format!("Picked {name}"));
+                        ui.label(format!("Triangle {} {:?}", hit.triangle, hit.vertices));
+                        ui.label(format!(
+                            "Barycentric ({:.3}, {:.3}, {:.3})",
+                            hit.barycentric.x, hit.barycentric.y, hit.barycentric.z
+                        ));
+                        ui.label(format!(
+                            "Nearest vertex {}, edge ({a}, {b})",
+                            hit.nearest_vertex()
+                        ));
+                    }
+
+                    if !models.is_empty() {
+                        ui.separator();
+                        ui.heading("Models");
+
+                        let mut removed_model = None;
+
+                        for (model_index, loaded) in models.iter_mut().enumerate() {
+                            let mut transform = loaded.transform();
+                            let LoadedModel { parts, root, .. } = loaded;
+                            let id = ui.make_persistent_id(("model", model_index));
+
+                            CollapsingState::load_with_default_open(ui.ctx(), id, true)
+                                .show_header(ui, |ui| {
+                                    ui.checkbox(&mut root.visible, "");
+                                    ui.add(
+                                        TextEdit::singleline(&mut root.name).desired_width(140.0),
+                                    );
+
+                                    if ui.small_button("Remove").clicked() {
+                                        removed_model = Some(model_index);
+                                    }
+                                })
+                                .body(|ui| {
+                                    vec3_ui(ui, "Position", &mut transform.position, 0.01);
+                                    vec3_ui(ui, "Rotation", &mut transform.rotation, 1.0);
+                                    vec3_ui(ui, "Scale", &mut transform.scale, 0.01);
+
+                                    for (child_index, child) in root.children.iter_mut().enumerate()
+                                    {
+                                        model_node_ui(
+                                            ui,
+                                            id.with(child_index),
+                                            child,
+                                            parts,
+                                            model_index,
+                                            &mut selected_part,
+                                        );
+                                    }
+                                });
+
+                            if transform != loaded.transform() {
+                                loaded.set_transform(transform);
+                            }
+                        }
+
+                        if let Some(model_index) = removed_model {
+                            let model = models.remove(model_index);
+                            committed_transforms.remove(model_index);
+                            history.push(SceneEdit::RemoveModel {
+                                index: model_index,
+                                model: Some(model),
+                            });
+
+                            // indices of later models shift down by one
+                            selected_part = match selected_part {
+                                Some((index, _)) if index == model_index => None,
+                                Some((index, part)) if index > model_index => {
+                                    Some((index - 1, part))
+                                }
+                                other => other,
+                            };
+                            highlighted_part = None;
+                            selection_edges = None;
+                            picked = None;
                         }
                     }
                 });
 
-                panel_width = gui_context.used_rect().width();
+                if !error_messages.is_empty() {
+                    Window::new("Error")
+                        .collapsible(false)
+                        .resizable(false)
+                        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+                        .show(gui_context, |ui| {
+                            for message in error_messages.iter() {
+                                ui.label(message);
+                            }
+
+                            if ui.button("Close").clicked() {
+                                error_messages.clear();
+                            }
+                        });
+                }
+
+                // only the side panel takes space from the viewport, not floating windows
+                panel_width = side_panel.response.rect.width();
             },
         );
 
+        if let Some(buf) = project_request.take() {
+            match load_project(&buf) {
+                Ok(project) => {
+                    // the opened project replaces the scene, and what could be undone with it
+                    models.clear();
+                    committed_transforms.clear();
+                    history.clear();
+                    selected_part = None;
+                    highlighted_part = None;
+                    selection_edges = None;
+                    picked = None;
+                    selected_control_point = None;
+
+                    camera_transition = None;
+                    riding = false;
+                    frame_loaded_models = false;
+                    projection = project.projection;
+                    projection.apply(&mut camera);
+                    project.camera.apply(&mut camera);
+                    control.target = project.camera.target;
+
+                    lights = project.lights;
+
+                    let display = project.display;
+                    show_axes = display.axes;
+                    show_debug_arrow = display.debug_arrow;
+                    show_debug_sphere = display.debug_sphere;
+                    sphere_position = display.sphere_position;
+                    show_tube = display.tube;
+                    show_tube_vertices = display.tube_vertices;
+                    show_tube_indices = display.tube_edges;
+                    show_tube_transparent = display.tube_transparent;
+                    show_tube_arrows = display.tube_arrows;
+
+                    tube_parameters = project.tube_parameters;
+                    committed_tube_parameters = tube_parameters;
+
+                    let mut files: Vec<PathBuf> = project
+                        .models
+                        .iter()
+                        .map(|model| model.path.clone())
+                        .collect();
+
+                    editing_curve = false;
+                    pending_curve = None;
+
+                    match project.curve {
+                        ProjectCurve::Default => {
+                            curve = Box::new(SineCurve);
+                            curve_source = ProjectCurve::Default;
+                            built_tube_parameters = None;
+                        }
+                        ProjectCurve::File(path) => {
+                            // read once when it is also one of the models
+                            if !files.contains(&path) {
+                                files.push(path.clone());
+                            }
+                            pending_curve = Some(path);
+                        }
+                        ProjectCurve::Spline(project_spline) => {
+                            spline = project_spline;
+                            built_spline = None;
+                            editing_curve = true;
+                        }
+                    }
+
+                    committed_spline = spline.clone();
+                    pending_models = project.models;
+
+                    if !files.is_empty() {
+                        loading = true;
+
+                        let sender = load_sender.clone();
+
+                        std::thread::spawn(move || {
+                            for buf in files {
+                                let contents = read_file(&buf);
+                                let _ = sender.send(Some((buf, contents)));
+                            }
+
+                            let _ = sender.send(None);
+                        });
+                    }
+                }
+                Err(error) => {
+                    error_messages.push(format!("Unable to open {:?}: {}", buf, error));
+                }
+            }
+        }
+
+        // files read by the loader thread
+        while let Ok(loaded) = load_receiver.try_recv() {
+            let Some((buf, contents)) = loaded else {
+                loading = false;
+                continue;
+            };
+
+            // a project keeps its own tube parameters for its curve file
+            let project_curve = pending_curve.take_if(|path| *path == buf).is_some();
+
+            // point files describe a path for the tube, not a mesh
+            if let Some(point_list) = contents.points {
+                if let Some(radius) = point_list.mean_radius()
+                    && args.radius.is_none()
+                    && !project_curve
+                {
+                    tube_parameters.radius = radius;
+                }
+                curve = Box::new(point_list.to_catmull_rom(tube_parameters.closed));
+                curve_source = ProjectCurve::File(buf.clone());
+                built_tube_parameters = None;
+                show_tube |= !project_curve;
+                editing_curve = false;
+            }
+
+            if contents.curves.len() > 1 {
+                error_messages.push(format!(
+                    "{:?}: only the first of {} curves is used",
+                    buf,
+                    contents.curves.len()
+                ));
+            }
+
+            if let Some(obj_curve) = contents.curves.into_iter().next() {
+                curve = obj_curve.curve;
+                curve_source = ProjectCurve::File(buf.clone());
+                built_tube_parameters = None;
+                show_tube |= !project_curve;
+                editing_curve = false;
+            }
+
+            let project_model = pending_models
+                .iter()
+                .position(|model| model.path == buf)
+                .map(|index| pending_models.remove(index));
+
+            // a failed load keeps the current scene as it is, and the faces of a
+            // project's curve file are only shown if it saved them as a model
+            if let Some(scene) = contents.scene
+                && (project_model.is_some() || !project_curve)
+            {
+                let mut loaded = LoadedModel::new(&context, &buf, scene);
+
+                // models of an opened project are restored as saved, not recorded as edits
+                if let Some(project_model) = project_model {
+                    loaded.root.name = project_model.name;
+                    loaded.root.visible = project_model.visible;
+                    loaded.set_transform(project_model.transform);
+
+                    models.push(loaded);
+                    committed_transforms.push(project_model.transform);
+                } else {
+                    models.push(loaded);
+                    committed_transforms.push(ModelTransform::default());
+                    history.push(SceneEdit::AddModel {
+                        index: models.len() - 1,
+                        model: None,
+                    });
+                }
+            }
+
+            for error in contents.errors {
+                error_messages.push(format!("Unable to load {:?}: {}", buf, error));
+            }
+        }
+
+        if undo_requested || redo_requested {
+            let model_count = models.len();
+            let mut target = EditTarget {
+                models: &mut models,
+                spline: &mut spline,
+                tube_parameters: &mut tube_parameters,
+            };
+
+            let changed = if undo_requested {
+                history.undo(&mut target)
+            } else {
+                history.redo(&mut target)
+            };
+
+            undo_requested = false;
+            redo_requested = false;
+
+            if changed {
+                // indices into the model list may point elsewhere now
+                if models.len() != model_count {
+                    selected_part = None;
+                    highlighted_part = None;
+                    selection_edges = None;
+                    picked = None;
+                }
+
+                selected_control_point =
+                    selected_control_point.filter(|&index| index < spline.points.len());
+
+                // shows the curve the edit was made to
+                if spline != committed_spline {
+                    editing_curve = true;
+                    show_tube = true;
+                }
+
+                committed_transforms = models.iter().map(LoadedModel::transform).collect();
+                committed_spline = spline.clone();
+                committed_tube_parameters = tube_parameters;
+            }
+        }
+
         let viewport = Viewport {
             x: (panel_width * frame_input.device_pixel_ratio) as i32,
             y: 0,
@@ -347,7 +1904,164 @@ fn main() {
         };
 
         camera.set_viewport(viewport);
-        control.handle_events(&mut camera, &mut frame_input.events);
+
+        if frame_loaded_models && !loading {
+            frame_loaded_models = false;
+
+            let pose = args.camera_pose(&models, viewport.aspect());
+            pose.apply(&mut camera);
+            control.target = pose.target;
+        }
+
+        if highlighted_part != selected_part {
+            highlighted_part = selected_part;
+
+            selection_edges = selected_part.map(|(model_index, part_index)| {
+                let part = &models[model_index].parts[part_index];
+
+                Gm::new(
+                    InstancedMesh::new(&context, &edge_transformations(&part.cpu_mesh), &cylinder),
+                    selection_material.clone(),
+                )
+            });
+        }
+
+        // follows the model when it is moved in the outliner
+        if let (Some(edges), Some((model_index, part_index))) =
+            (selection_edges.as_mut(), selected_part)
+        {
+            edges.set_transformation(models[model_index].part_transformation(part_index));
+        }
+
+        // what the gizmo edits, in order of preference
+        let gizmo_point = selected_control_point.filter(|_| editing_curve);
+        let gizmo_model = selected_part.map(|(model_index, _)| model_index);
+
+        let mut gizmo_transform = match (gizmo_point, gizmo_model) {
+            (Some(index), _) => Some(ModelTransform {
+                position: spline.points[index],
+                ..Default::default()
+            }),
+            (None, Some(model_index)) => Some(models[model_index].transform()),
+            (None, None) if show_debug_sphere => Some(ModelTransform {
+                position: sphere_position,
+                ..Default::default()
+            }),
+            (None, None) => None,
+        };
+
+        gizmo.translate_only = gizmo_model.is_none() || gizmo_point.is_some();
+
+        if let Some(transform) = gizmo_transform.as_mut()
+            && gizmo.handle_events(&camera, &mut frame_input.events, transform)
+        {
+            match (gizmo_point, gizmo_model) {
+                // only the position of points and the sphere can be edited
+                (Some(index), _) => spline.points[index] = transform.position,
+                (None, Some(model_index)) => models[model_index].set_transform(*transform),
+                (None, None) => sphere_position = transform.position,
+            }
+        }
+
+        // a left click that did not move picks, dragging still orbits
+        for event in frame_input.events.iter() {
+            match *event {
+                three_d::Event::MousePress {
+                    button: MouseButton::Left,
+                    position,
+                    handled: false,
+                    ..
+                } => press_position = Some(position),
+                three_d::Event::MouseRelease {
+                    button: MouseButton::Left,
+                    position,
+                    modifiers,
+                    handled: false,
+                } => {
+                    if let Some(pressed) = press_position.take()
+                        && (pressed.x - position.x).abs() + (pressed.y - position.y).abs() < 4.0
+                    {
+                        let ray = Ray::from_pixel(&camera, position);
+
+                        if editing_curve {
+                            let grab_distance =
+                                |point: Vec3| (point - camera.position()).magnitude() * 0.02;
+
+                            if let Some(index) = spline.pick(&ray, grab_distance) {
+                                selected_control_point = Some(index);
+                                continue;
+                            }
+
+                            if modifiers.shift {
+                                // on the plane facing the camera through the point it
+                                // follows, or through the target for the first point
+                                let after = spline.insertion_point(selected_control_point);
+                                let anchor =
+                                    after.map_or(control.target, |after| spline.points[after]);
+                                let index = after.map_or(0, |after| after + 1);
+
+                                if let Some(t) =
+                                    ray.intersect_plane(anchor, camera.view_direction())
+                                {
+                                    spline.points.insert(index, ray.at(t));
+                                    selected_control_point = Some(index);
+                                }
+
+                                continue;
+                            }
+
+                            selected_control_point = None;
+                        }
+
+                        picked = pick(&ray, &models, show_tube.then_some(&tube_bvh));
+
+                        selected_part = match picked.map(|hit| hit.target) {
+                            Some(PickTarget::Part { model, part }) => Some((model, part)),
+                            _ => None,
+                        };
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // a standard view or framing ends the ride
+        if let Some(pose) = camera_request.take() {
+            riding = false;
+            camera_transition = Some(CameraTransition::new(
+                CameraPose::of(&camera, control.target),
+                pose,
+                CAMERA_TRANSITION_SECONDS,
+            ));
+        }
+
+        if let Some(transition) = &mut camera_transition {
+            let pose = transition.update(frame_input.elapsed_time as f32 / 1000.0);
+            pose.apply(&mut camera);
+            control.target = pose.target;
+
+            if transition.is_finished() {
+                camera_transition = None;
+            }
+        }
+
+        // navigating by hand stops an animation
+        if control.handle_events(
+            &mut camera,
+            &mut frame_input.events,
+            frame_input.elapsed_time as f32 / 1000.0,
+        ) {
+            camera_transition = None;
+        }
+
+        // the ride takes over the camera
+        if riding {
+            fly_through.update(frame_input.elapsed_time as f32 / 1000.0);
+
+            let pose = fly_through.pose();
+            pose.apply(&mut camera);
+            control.target = pose.target;
+        }
 
         if show_debug_sphere {
             debug_sphere.set_transformation(Mat4::from_translation(sphere_position));
@@ -362,26 +2076,131 @@ fn main() {
             arrow.set_transformation(brr);
         }
 
-        // tube
-        // horrible performance
-        if show_tube {
-            let tube = Tube::new(
-                &curve,
-                tubular_segments,
-                tubular_closed,
-                tubular_radius,
-                tubular_radial_segments,
-            );
+        if !pointer_down {
+            for (index, loaded) in models.iter().enumerate() {
+                if loaded.transform() != committed_transforms[index] {
+                    history.push(SceneEdit::Transform {
+                        model: index,
+                        before: committed_transforms[index],
+                        after: loaded.transform(),
+                    });
+                    committed_transforms[index] = loaded.transform();
+                }
+            }
 
-            cpu_tube = CpuMesh {
-                positions: three_d::Positions::F32(tube.vertices),
-                indices: three_d::Indices::U32(tube.indices),
+            if editing_curve && spline != committed_spline {
+                history.push(SceneEdit::Curve {
+                    before: committed_spline.clone(),
+                    after: spline.clone(),
+                });
+                committed_spline = spline.clone();
+            }
+
+            if tube_parameters != committed_tube_parameters {
+                history.push(SceneEdit::TubeParameters {
+                    before: committed_tube_parameters,
+                    after: tube_parameters,
+                });
+                committed_tube_parameters = tube_parameters;
+            }
+        }
+
+        // the tube follows the control points while they are edited
+        if editing_curve && built_spline != Some((spline.clone(), tube_parameters.closed)) {
+            built_spline = Some((spline.clone(), tube_parameters.closed));
+
+            if let Some(edited) = spline.to_curve(tube_parameters.closed) {
+                curve = edited;
+                curve_source = ProjectCurve::Spline(spline.clone());
+                built_tube_parameters = None;
+            }
+
+            control_polygon.geometry.set_instances(&Instances {
+                transformations: spline
+                    .points
+                    .windows(2)
+                    .map(|pair| edge_transform(pair[0], pair[1]))
+                    .collect(),
                 ..Default::default()
-            };
+            });
+        }
 
-            cpu_tube.compute_normals();
+        if editing_curve {
+            // the selected point stands out
+            control_points.geometry.set_instances(&Instances {
+                transformations: spline
+                    .points
+                    .iter()
+                    .map(|&point| Mat4::from_translation(point))
+                    .collect(),
+                colors: Some(
+                    (0..spline.points.len())
+                        .map(|index| {
+                            if selected_control_point == Some(index) {
+                                Srgba::new_opaque(255, 220, 0)
+                            } else {
+                                Srgba::new_opaque(230, 90, 20)
+                            }
+                        })
+                        .collect(),
+                ),
+                ..Default::default()
+            });
+        }
+
+        // tube, only rebuilt when its path or parameters changed
+        if show_tube && built_tube_parameters != Some(tube_parameters) {
+            // the radius and radial segments leave the path and its frames as they are
+            let path_changed =
+                built_tube_parameters.is_none_or(|built| !built.same_path(&tube_parameters));
+            built_tube_parameters = Some(tube_parameters);
+
+            let tube = Tube::from_parameters(curve.as_ref(), &tube_parameters);
+
+            cpu_tube = tube.to_cpu_mesh();
+            tube_bvh = Bvh::new(&cpu_tube);
 
-            gm_tube = Gm::new(Mesh::new(&context, &cpu_tube), default_material.clone());
+            if picked.is_some_and(|hit| hit.target == PickTarget::Tube) {
+                picked = None;
+            }
+
+            // keeps the current material
+            gm_tube.geometry = Mesh::new(&context, &cpu_tube);
+            shadows_stale = true;
+
+            if path_changed {
+                if riding {
+                    fly_through.set_curve(curve.as_ref(), tube_parameters.closed);
+                }
+
+                tube_arrows =
+                    frame_arrows(&context, &tube.center_points, &tube.frame, &arrow_style);
+            }
+
+            tube_edges_stale = true;
+            tube_vertices_stale = true;
+        }
+
+        if show_tube && show_tube_indices && tube_edges_stale {
+            tube_edges_stale = false;
+
+            edges = Gm::new(
+                InstancedMesh::new(&context, &edge_transformations(&cpu_tube), &cylinder),
+                wireframe_material.clone(),
+            );
+        }
+
+        if show_tube && show_tube_vertices && tube_vertices_stale {
+            tube_vertices_stale = false;
+
+            vertices = Gm::new(
+                InstancedMesh::new(&context, &vertex_transformations(&cpu_tube), &sphere),
+                wireframe_material.clone(),
+            );
+        }
+
+        if built_tube_transparent != show_tube_transparent {
+            built_tube_transparent = show_tube_transparent;
 
             if show_tube_transparent {
                 gm_tube.material = transparent_material.clone();
@@ -390,127 +2209,355 @@ fn main() {
                 gm_tube.material = default_material.clone();
                 gm_tube.material.render_states.cull = Default::default();
             }
+        }
 
-            tube_arrows.clear();
+        if highlighted_pick != picked {
+            highlighted_pick = picked;
 
-            for (curve_index, point) in tube.center_points.iter().enumerate() {
-                let mut arrow = CpuMesh::arrow(0.9, 0.5, 16);
-                arrow.transform(arrow_scale).unwrap();
+            pick_highlight = picked
+                .map(|hit| {
+                    let positions = match hit.target {
+                        PickTarget::Part { model, part } => {
+                            models[model].parts[part].bvh.positions()
+                        }
+                        PickTarget::Tube => tube_bvh.positions(),
+                    };
+                    let [a, b, c] = hit.vertices.map(|vertex| positions[vertex]);
+                    let (start, end) = hit.nearest_edge();
 
-                let rotation = arrow_to_dir_pos(Point3::origin(), tube.tangents_frame[curve_index]);
-                arrow.transform(rotation).unwrap();
+                    let instances = |transformations: Vec<Mat4>| Instances {
+                        transformations,
+                        ..Default::default()
+                    };
+
+                    vec![
+                        Gm::new(
+                            InstancedMesh::new(
+                                &context,
+                                &instances(vec![
+                                    edge_transform(a, b),
+                                    edge_transform(b, c),
+                                    edge_transform(c, a),
+                                ]),
+                                &cylinder,
+                            ),
+                            pick_material.clone(),
+                        ),
+                        Gm::new(
+                            InstancedMesh::new(
+                                &context,
+                                &instances(vec![edge_transform(positions[start], positions[end])]),
+                                &cylinder,
+                            ),
+                            nearest_material.clone(),
+                        ),
+                        Gm::new(
+                            InstancedMesh::new(
+                                &context,
+                                &instances(vec![Mat4::from_translation(
+                                    positions[hit.nearest_vertex()],
+                                )]),
+                                &sphere,
+                            ),
+                            nearest_material.clone(),
+                        ),
+                    ]
+                })
+                .unwrap_or_default();
+        }
 
-                arrow.transform(Mat4::from_translation(*point)).unwrap();
+        // follows the model like the selection edges
+        if let Some(hit) = picked {
+            let transformation = match hit.target {
+                PickTarget::Part { model, part } => models[model].part_transformation(part),
+                PickTarget::Tube => Mat4::identity(),
+            };
 
-                tube_arrows.push(Gm::new(
-                    Mesh::new(&context, &arrow),
-                    PhysicalMaterial {
-                        albedo: Srgba {
-                            r: 255,
-                            g: 255,
-                            b: 0,
-                            a: 0,
-                        },
-                        ..Default::default()
-                    },
-                ));
+            for highlight in pick_highlight.iter_mut() {
+                highlight.set_transformation(transformation);
             }
+        }
 
-            for (curve_index, point) in tube.center_points.iter().enumerate() {
-                let mut arrow = CpuMesh::arrow(0.9, 0.5, 16);
-                arrow.transform(arrow_scale).unwrap();
+        // everything drawn in the viewport
+        if built_lights != lights {
+            built_lights = lights.clone();
+            scene_lights = lights
+                .iter()
+                .map(|light| SceneLight::new(&context, light))
+                .collect();
+            shadows_stale = true;
+        }
 
-                let rotation = arrow_to_dir_pos(Point3::origin(), tube.normals_frame[curve_index]);
-                arrow.transform(rotation).unwrap();
+        // shadows follow the models and tube as they move or change
+        let casters = ShadowCasters::new(&models, show_tube);
 
-                arrow.transform(Mat4::from_translation(*point)).unwrap();
+        if lights.iter().any(LightSettings::casts_shadows)
+            && (shadows_stale || shadow_casters.as_ref() != Some(&casters))
+        {
+            shadows_stale = false;
+            shadow_casters = Some(casters);
 
-                tube_arrows.push(Gm::new(
-                    Mesh::new(&context, &arrow),
-                    PhysicalMaterial {
-                        albedo: Srgba {
-                            r: 190,
-                            g: 100,
-                            b: 0,
-                            a: 0,
-                        },
-                        ..Default::default()
-                    },
-                ));
+            let mut geometries: Vec<&dyn Geometry> = Vec::new();
+
+            for loaded in models.iter() {
+                loaded.for_each_visible_part(|part| geometries.push(&part.gm.geometry));
             }
 
-            for (curve_index, point) in tube.center_points.iter().enumerate() {
-                let mut arrow = CpuMesh::arrow(0.9, 0.5, 16);
-                arrow.transform(arrow_scale).unwrap();
+            if show_tube {
+                geometries.push(&gm_tube.geometry);
+            }
 
-                let rotation =
-                    arrow_to_dir_pos(Point3::origin(), tube.binormals_frame[curve_index]);
-                arrow.transform(rotation).unwrap();
+            for (scene_light, light) in scene_lights.iter_mut().zip(lights.iter()) {
+                if light.casts_shadows() {
+                    scene_light.generate_shadow_map(&geometries);
+                }
+            }
+        }
 
-                arrow.transform(Mat4::from_translation(*point)).unwrap();
+        let active_lights: Vec<&dyn Light> = scene_lights.iter().map(SceneLight::light).collect();
 
-                tube_arrows.push(Gm::new(
-                    Mesh::new(&context, &arrow),
-                    PhysicalMaterial {
-                        albedo: Srgba::GREEN,
-                        ..Default::default()
-                    },
-                ));
-            }
+        let mut objects: Vec<&dyn Object> = Vec::new();
 
-            edges = Gm::new(
-                InstancedMesh::new(&context, &edge_transformations(&cpu_tube), &cylinder),
-                wireframe_material.clone(),
-            );
+        if show_axes {
+            objects.push(&axes);
+        }
 
-            vertices = Gm::new(
-                InstancedMesh::new(&context, &vertex_transformations(&cpu_tube), &sphere),
-                wireframe_material.clone(),
+        if show_debug_arrow {
+            objects.push(&arrow);
+        }
+
+        if show_debug_sphere {
+            objects.push(&debug_sphere);
+        }
+
+        for loaded in models.iter() {
+            loaded.for_each_visible_part(|part| objects.push(&part.gm));
+        }
+
+        if let Some(selection_edges) = &selection_edges {
+            objects.push(selection_edges);
+        }
+
+        if show_tube || picked.is_some_and(|hit| hit.target != PickTarget::Tube) {
+            objects.extend(
+                pick_highlight
+                    .iter()
+                    .map(|highlight| highlight as &dyn Object),
             );
         }
 
-        frame_input
-            .screen()
+        if show_tube {
+            objects.push(&gm_tube);
+
+            if show_tube_vertices {
+                objects.push(&vertices);
+            }
+
+            if show_tube_indices {
+                objects.push(&edges);
+            }
+
+            if show_tube_arrows {
+                objects.extend(tube_arrows.iter().map(|arrow| arrow as &dyn Object));
+            }
+        }
+
+        let screen = frame_input.screen();
+
+        screen
             .clear(ClearState::color_and_depth(1., 1., 1., 1.0, 1.0))
-            .write(|| {
-                if show_axes {
-                    axes.render(&camera, &[&ambient, &directional]);
-                }
+            .render(&camera, objects.iter(), &active_lights);
 
-                if show_debug_arrow {
-                    arrow.render(&camera, &[&ambient, &directional]);
-                }
+        // over the scene, but not in screenshots or turntables
+        if editing_curve {
+            screen.render(&camera, [&control_points, &control_polygon], &active_lights);
+        }
+
+        if let Some(transform) = &gizmo_transform {
+            screen.render(&camera, gizmo.objects(&camera, transform), &[]);
+        }
 
-                if show_debug_sphere {
-                    debug_sphere.render(&camera, &[&ambient, &directional]);
+        screen.write(|| gui.render()).unwrap();
+
+        if screenshot_requested {
+            screenshot_requested = false;
+
+            let image = if screenshot_include_gui {
+                // the whole window as it was just drawn, panel included
+                CpuTexture {
+                    data: TextureData::RgbaU8(screen.read_color()),
+                    width: frame_input.viewport.width,
+                    height: frame_input.viewport.height,
+                    ..Default::default()
                 }
+            } else {
+                let width = viewport.width * screenshot_scale;
+                let height = viewport.height * screenshot_scale;
+
+                let mut screenshot_camera = camera.clone();
+                screenshot_camera.set_viewport(Viewport::new_at_origo(width, height));
+
+                render_offscreen(
+                    &context,
+                    width,
+                    height,
+                    ClearState::color_and_depth(1., 1., 1., 1.0, 1.0),
+                    |target| {
+                        target.render(&screenshot_camera, objects.iter(), &active_lights);
+                    },
+                )
+            };
 
-                if show_tube {
-                    gm_tube.render(&camera, &[&ambient, &directional]);
+            let seconds = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default();
+            let buf = current_dir.join(format!("screenshot-{seconds}.png"));
 
-                    if show_tube_vertices {
-                        for vertex in vertices.into_iter() {
-                            vertex.render(&camera, &[&ambient, &directional]);
-                        }
-                    }
+            if let Err(error) = save_png(&buf, &image) {
+                error_messages.push(format!("Unable to save {:?}: {}", buf, error));
+            }
+        }
 
-                    if show_tube_indices {
-                        for edge in edges.into_iter() {
-                            edge.render(&camera, &[&ambient, &directional]);
-                        }
+        if let Some(buf) = turntable_request.take() {
+            let mut turntable_camera = camera.clone();
+            turntable_camera.set_viewport(Viewport::new_at_origo(viewport.width, viewport.height));
+
+            match TurntableExport::create(
+                &buf,
+                turntable_frames,
+                turntable_gif,
+                &turntable_camera,
+                control.target,
+            ) {
+                Ok(export) => turntable_export = Some(export),
+                Err(error) => error_messages.push(format!("Unable to export {:?}: {}", buf, error)),
+            }
+        }
+
+        // a single frame per redraw keeps the viewer responsive, the scene as
+        // it is now is what gets rendered
+        if let Some(export) = turntable_export.as_mut() {
+            match export.next_camera() {
+                Some(turntable_camera) => {
+                    let (width, height) = (
+                        turntable_camera.viewport().width,
+                        turntable_camera.viewport().height,
+                    );
+                    let image = render_offscreen(
+                        &context,
+                        width,
+                        height,
+                        ClearState::color_and_depth(1., 1., 1., 1.0, 1.0),
+                        |target| {
+                            target.render(&turntable_camera, objects.iter(), &active_lights);
+                        },
+                    );
+
+                    if let Err(error) = export.add_frame(&image) {
+                        error_messages
+                            .push(format!("Unable to export {:?}: {}", export.path, error));
+                        turntable_export = None;
                     }
+                }
+                None => turntable_export = None,
+            }
+        }
 
-                    if show_tube_arrows {
-                        for arrow in tube_arrows.iter() {
-                            arrow.render(&camera, &[&ambient, &directional]);
-                        }
+        if fullscreen != window.fullscreen().is_some() {
+            window.set_fullscreen(fullscreen.then_some(Fullscreen::Borderless(None)));
+        }
+    };
+
+    // the window is driven directly, rather than through `Window::render_loop`,
+    // to toggle fullscreen and read its size on close
+    event_loop.run(move |event, _, control_flow| match event {
+        Event::MainEventsCleared => window.request_redraw(),
+        Event::RedrawRequested(_) => {
+            render_frame(frame_input_generator.generate(&windowed_context), &window);
+            windowed_context.swap_buffers().unwrap();
+            *control_flow = ControlFlow::Poll;
+        }
+        Event::WindowEvent { ref event, .. } => {
+            frame_input_generator.handle_winit_window_event(event);
+
+            match event {
+                WindowEvent::Resized(physical_size) => windowed_context.resize(*physical_size),
+                WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                    windowed_context.resize(**new_inner_size)
+                }
+                WindowEvent::CloseRequested => {
+                    let size = window.inner_size().to_logical::<u32>(window.scale_factor());
+
+                    // the fullscreen size is the monitor's, keep the last windowed one
+                    let window_size = match (window.fullscreen(), settings) {
+                        (Some(_), Some(settings)) => settings.window_size,
+                        (Some(_), None) => (WINDOW_WIDTH, WINDOW_HEIGHT),
+                        (None, _) => (size.width, size.height),
+                    };
+
+                    if let Err(error) = save_settings(&ViewerSettings {
+                        window_size,
+                        fullscreen: window.fullscreen().is_some(),
+                    }) {
+                        eprintln!("Unable to save viewer settings: {error}");
                     }
+
+                    *control_flow = ControlFlow::Exit;
                 }
+                _ => (),
+            }
+        }
+        _ => (),
+    })
+}
 
-                gui.render()
-            })
-            .unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn camera_vectors_accept_negative_components() {
+        let args = Args::try_parse_from([
+            "main",
+            "--camera-position",
+            "-1,0,2",
+            "--camera-target",
+            "-0.5,-1,-2",
+            "model.obj",
+        ])
+        .unwrap();
 
-        FrameOutput::default()
-    });
+        assert_eq!(args.camera_position, Some(vec3(-1.0, 0.0, 2.0)));
+        assert_eq!(args.camera_target, Some(vec3(-0.5, -1.0, -2.0)));
+        assert_eq!(args.files, [PathBuf::from("model.obj")]);
+    }
+
+    #[test]
+    fn camera_vectors_need_three_components() {
+        assert!(Args::try_parse_from(["main", "--camera-position", "-1,0"]).is_err());
+    }
+
+    #[test]
+    fn tube_segments_must_be_at_least_one() {
+        let args = Args::try_parse_from(["main", "--radial-segments", "1"]).unwrap();
+        assert_eq!(args.radial_segments, 1);
+
+        assert!(Args::try_parse_from(["main", "--radial-segments", "0"]).is_err());
+        assert!(Args::try_parse_from(["main", "--tubular-segments", "0"]).is_err());
+        assert!(Args::try_parse_from(["main", "--tubular-segments", "-3"]).is_err());
+    }
+
+    #[test]
+    fn radius_must_be_positive_and_finite() {
+        let args = Args::try_parse_from(["main", "--radius", "0.25"]).unwrap();
+        assert_eq!(args.radius, Some(0.25));
+
+        for radius in ["0", "-1", "inf", "NaN", "wide"] {
+            assert!(
+                Args::try_parse_from(["main", "--radius", radius]).is_err(),
+                "{radius}"
+            );
+        }
+    }
 }
@@ -0,0 +1,810 @@
+use std::cell::Cell;
+
+use three_d::{AxisAlignedBoundingBox, CpuMesh, InnerSpace, Mat4, Vec3, vec3};
+
+use crate::ray::Ray;
+
+/// Number of buckets the surface area heuristic evaluates splits with, per axis.
+const BINS: usize = 12;
+/// Leaves may hold more triangles than this only when no split is cheaper.
+const MAX_LEAF_SIZE: usize = 8;
+/// Cost of visiting a node relative to intersecting one triangle.
+const TRAVERSAL_COST: f32 = 1.0;
+
+/// Where a ray meets a triangle of a [`Bvh`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TriangleHit {
+    /// Index of the triangle in the mesh.
+    pub triangle: usize,
+    /// Vertex indices of the triangle in the mesh.
+    pub vertices: [usize; 3],
+    /// Distance along the ray.
+    pub t: f32,
+    /// Weights of the three vertices at the hit point.
+    pub barycentric: Vec3,
+}
+
+/// The point of a [`Bvh`] closest to a query point.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClosestPoint {
+    pub triangle: usize,
+    pub point: Vec3,
+    pub distance: f32,
+    /// Weights of the triangle vertices at `point`.
+    pub barycentric: Vec3,
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    bounds: AxisAlignedBoundingBox,
+    kind: NodeKind,
+}
+
+/// Children for inner nodes, a range of `order` for leaves.
+#[derive(Clone, Copy, Debug)]
+enum NodeKind {
+    Inner(usize, usize),
+    Leaf(usize, usize),
+}
+
+/// A bounding volume hierarchy over the triangles of a mesh, in the space of its positions.
+///
+/// Built with a binned surface area heuristic. Moving vertices keeps the tree and only
+/// [refits](Bvh::refit) its bounds, which stays fast but loosens it when the mesh deforms a lot.
+#[derive(Clone, Debug)]
+pub struct Bvh {
+    positions: Vec<Vec3>,
+    triangles: Vec<[usize; 3]>,
+    /// Triangle indices, grouped by leaf.
+    order: Vec<usize>,
+    nodes: Vec<Node>,
+}
+
+impl Bvh {
+    pub fn new(cpu_mesh: &CpuMesh) -> Self {
+        let mut triangles = Vec::new();
+        cpu_mesh.for_each_triangle(|a, b, c| triangles.push([a, b, c]));
+
+        Self::from_triangles(cpu_mesh.positions.to_f32(), triangles)
+    }
+
+    /// Builds over triangles given as indices into `positions`.
+    pub fn from_triangles(positions: Vec<Vec3>, triangles: Vec<[usize; 3]>) -> Self {
+        let mut bvh = Self {
+            positions,
+            order: (0..triangles.len()).collect(),
+            triangles,
+            nodes: Vec::new(),
+        };
+
+        if !bvh.triangles.is_empty() {
+            let centroids = bvh.centroids();
+            bvh.build(&centroids, 0, bvh.triangles.len());
+        }
+
+        bvh
+    }
+
+    pub fn positions(&self) -> &[Vec3] {
+        &self.positions
+    }
+
+    pub fn triangles(&self) -> &[[usize; 3]] {
+        &self.triangles
+    }
+
+    /// Bounds of the whole mesh.
+    pub fn bounds(&self) -> AxisAlignedBoundingBox {
+        self.nodes
+            .first()
+            .map_or(AxisAlignedBoundingBox::EMPTY, |node| node.bounds)
+    }
+
+    /// Replaces the vertex positions, keeping the triangles and the tree.
+    ///
+    /// # Panics
+    ///
+    /// If the number of positions changed.
+    pub fn refit(&mut self, positions: Vec<Vec3>) {
+        assert_eq!(
+            positions.len(),
+            self.positions.len(),
+            "refitting needs the same vertices"
+        );
+
+        self.positions = positions;
+
+        // children always come after their parent
+        for index in (0..self.nodes.len()).rev() {
+            self.nodes[index].bounds = match self.nodes[index].kind {
+                NodeKind::Inner(left, right) => {
+                    let mut bounds = self.nodes[left].bounds;
+                    bounds.expand_with_aabb(self.nodes[right].bounds);
+                    bounds
+                }
+                NodeKind::Leaf(start, end) => self.triangle_bounds(&self.order[start..end]),
+            };
+        }
+    }
+
+    /// Moves every vertex by `transformation` and refits.
+    pub fn transform(&mut self, transformation: Mat4) {
+        let positions = self
+            .positions
+            .iter()
+            .map(|position| (transformation * position.extend(1.0)).truncate())
+            .collect();
+
+        self.refit(positions);
+    }
+
+    /// The closest triangle hit by the ray, from either side.
+    pub fn intersect(&self, ray: &Ray) -> Option<TriangleHit> {
+        // read while pruning nodes and written by the leaves
+        let closest: Cell<Option<TriangleHit>> = Cell::new(None);
+
+        self.traverse(
+            |bounds| {
+                ray.intersect_aabb(bounds)
+                    .is_some_and(|t| closest.get().is_none_or(|hit| t <= hit.t))
+            },
+            |triangle| {
+                if let Some(hit) = self.intersect_triangle(ray, triangle)
+                    && closest.get().is_none_or(|closest| hit.t < closest.t)
+                {
+                    closest.set(Some(hit));
+                }
+            },
+        );
+
+        closest.get()
+    }
+
+    /// The point on the mesh closest to `point`, `None` for an empty mesh.
+    pub fn closest_point(&self, point: Vec3) -> Option<ClosestPoint> {
+        let closest: Cell<Option<ClosestPoint>> = Cell::new(None);
+
+        self.traverse(
+            |bounds| {
+                closest.get().is_none_or(|closest| {
+                    distance_squared_to_aabb(bounds, point) <= closest.distance * closest.distance
+                })
+            },
+            |triangle| {
+                let [a, b, c] = self.corners(triangle);
+                let barycentric = closest_on_triangle(point, a, b, c);
+                let on_triangle = a * barycentric.x + b * barycentric.y + c * barycentric.z;
+                let distance = (on_triangle - point).magnitude();
+
+                if closest
+                    .get()
+                    .is_none_or(|closest| distance < closest.distance)
+                {
+                    closest.set(Some(ClosestPoint {
+                        triangle,
+                        point: on_triangle,
+                        distance,
+                        barycentric,
+                    }));
+                }
+            },
+        );
+
+        closest.get()
+    }
+
+    /// Indices of the triangles that touch the box, in no particular order.
+    pub fn overlapping(&self, aabb: &AxisAlignedBoundingBox) -> Vec<usize> {
+        let mut triangles = Vec::new();
+
+        if aabb.is_empty() {
+            return triangles;
+        }
+
+        self.traverse(
+            |bounds| aabbs_overlap(bounds, aabb),
+            |triangle| {
+                if triangle_overlaps_aabb(self.corners(triangle), aabb) {
+                    triangles.push(triangle);
+                }
+            },
+        );
+
+        triangles
+    }
+
+    /// Visits the triangles of every leaf reachable through nodes accepted by `enter`.
+    fn traverse(
+        &self,
+        mut enter: impl FnMut(&AxisAlignedBoundingBox) -> bool,
+        mut visit: impl FnMut(usize),
+    ) {
+        let mut stack = Vec::new();
+
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+
+            if !enter(&node.bounds) {
+                continue;
+            }
+
+            match node.kind {
+                NodeKind::Inner(left, right) => stack.extend([right, left]),
+                NodeKind::Leaf(start, end) => {
+                    for &triangle in &self.order[start..end] {
+                        visit(triangle);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Builds the node for `order[start..end]` and everything below it, returning its index.
+    fn build(&mut self, centroids: &[Vec3], start: usize, end: usize) -> usize {
+        let bounds = self.triangle_bounds(&self.order[start..end]);
+        let index = self.nodes.len();
+
+        self.nodes.push(Node {
+            bounds,
+            kind: NodeKind::Leaf(start, end),
+        });
+
+        let count = end - start;
+        if count <= 2 {
+            return index;
+        }
+
+        let Some((axis, split, cost)) = self.best_split(centroids, start, end, &bounds) else {
+            return index;
+        };
+
+        if cost >= count as f32 && count <= MAX_LEAF_SIZE {
+            return index;
+        }
+
+        let (low, high) = centroid_range(centroids, &self.order[start..end], axis);
+        let mut middle = start;
+
+        for i in start..end {
+            if bin(centroids[self.order[i]][axis], low, high) < split {
+                self.order.swap(i, middle);
+                middle += 1;
+            }
+        }
+
+        if middle == start || middle == end {
+            return index;
+        }
+
+        let left = self.build(centroids, start, middle);
+        let right = self.build(centroids, middle, end);
+        self.nodes[index].kind = NodeKind::Inner(left, right);
+
+        index
+    }
+
+    /// The cheapest split of `order[start..end]` between bins, as axis, first bin
+    /// on the right and the cost relative to intersecting one triangle.
+    fn best_split(
+        &self,
+        centroids: &[Vec3],
+        start: usize,
+        end: usize,
+        bounds: &AxisAlignedBoundingBox,
+    ) -> Option<(usize, usize, f32)> {
+        let parent_area = surface_area(bounds);
+        let mut best: Option<(usize, usize, f32)> = None;
+
+        if parent_area <= 0.0 {
+            return None;
+        }
+
+        for axis in 0..3 {
+            let (low, high) = centroid_range(centroids, &self.order[start..end], axis);
+
+            if high - low <= f32::EPSILON {
+                continue;
+            }
+
+            let mut bins = [(AxisAlignedBoundingBox::EMPTY, 0usize); BINS];
+
+            for &triangle in &self.order[start..end] {
+                let bin = &mut bins[bin(centroids[triangle][axis], low, high)];
+                bin.0.expand(&self.corners(triangle));
+                bin.1 += 1;
+            }
+
+            // area and count of everything right of each split, swept from the end
+            let mut right = [(0.0, 0usize); BINS];
+            let mut accumulated = (AxisAlignedBoundingBox::EMPTY, 0);
+
+            for split in (1..BINS).rev() {
+                accumulated.0.expand_with_aabb(bins[split].0);
+                accumulated.1 += bins[split].1;
+                right[split] = (surface_area(&accumulated.0), accumulated.1);
+            }
+
+            let mut left = (AxisAlignedBoundingBox::EMPTY, 0);
+
+            for split in 1..BINS {
+                left.0.expand_with_aabb(bins[split - 1].0);
+                left.1 += bins[split - 1].1;
+
+                if left.1 == 0 || right[split].1 == 0 {
+                    continue;
+                }
+
+                let cost = TRAVERSAL_COST
+                    + (surface_area(&left.0) * left.1 as f32
+                        + right[split].0 * right[split].1 as f32)
+                        / parent_area;
+
+                if best.is_none_or(|(_, _, best)| cost < best) {
+                    best = Some((axis, split, cost));
+                }
+            }
+        }
+
+        best
+    }
+
+    fn triangle_bounds(&self, triangles: &[usize]) -> AxisAlignedBoundingBox {
+        let mut bounds = AxisAlignedBoundingBox::EMPTY;
+
+        for &triangle in triangles {
+            bounds.expand(&self.corners(triangle));
+        }
+
+        bounds
+    }
+
+    fn centroids(&self) -> Vec<Vec3> {
+        (0..self.triangles.len())
+            .map(|triangle| {
+                let [a, b, c] = self.corners(triangle);
+                (a + b + c) / 3.0
+            })
+            .collect()
+    }
+
+    fn corners(&self, triangle: usize) -> [Vec3; 3] {
+        self.triangles[triangle].map(|vertex| self.positions[vertex])
+    }
+
+    /// Möller–Trumbore, without culling back faces.
+    fn intersect_triangle(&self, ray: &Ray, triangle: usize) -> Option<TriangleHit> {
+        let [a, b, c] = self.corners(triangle);
+        let edge1 = b - a;
+        let edge2 = c - a;
+
+        let p = ray.direction.cross(edge2);
+        let determinant = edge1.dot(p);
+
+        if determinant.abs() < 1e-9 {
+            return None;
+        }
+
+        let inverse = 1.0 / determinant;
+        let offset = ray.origin - a;
+
+        let u = offset.dot(p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = offset.cross(edge1);
+        let v = ray.direction.dot(q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = edge2.dot(q) * inverse;
+
+        (t >= 0.0).then_some(TriangleHit {
+            triangle,
+            vertices: self.triangles[triangle],
+            t,
+            barycentric: vec3(1.0 - u - v, u, v),
+        })
+    }
+}
+
+fn centroid_range(centroids: &[Vec3], triangles: &[usize], axis: usize) -> (f32, f32) {
+    triangles
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), &t| {
+            (low.min(centroids[t][axis]), high.max(centroids[t][axis]))
+        })
+}
+
+fn bin(value: f32, low: f32, high: f32) -> usize {
+    if high <= low {
+        return 0;
+    }
+
+    (((value - low) / (high - low) * BINS as f32) as usize).min(BINS - 1)
+}
+
+fn surface_area(aabb: &AxisAlignedBoundingBox) -> f32 {
+    if aabb.is_empty() {
+        return 0.0;
+    }
+
+    let size = aabb.size();
+    2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+}
+
+/// Unlike [`AxisAlignedBoundingBox::intersection`], boxes that only touch overlap too.
+pub fn aabbs_overlap(a: &AxisAlignedBoundingBox, b: &AxisAlignedBoundingBox) -> bool {
+    let (a_min, a_max, b_min, b_max) = (a.min(), a.max(), b.min(), b.max());
+
+    !a.is_empty()
+        && !b.is_empty()
+        && (0..3).all(|axis| a_min[axis] <= b_max[axis] && b_min[axis] <= a_max[axis])
+}
+
+fn distance_squared_to_aabb(aabb: &AxisAlignedBoundingBox, point: Vec3) -> f32 {
+    let (min, max) = (aabb.min(), aabb.max());
+
+    (0..3)
+        .map(|axis| {
+            let outside = (min[axis] - point[axis])
+                .max(point[axis] - max[axis])
+                .max(0.0);
+            outside * outside
+        })
+        .sum()
+}
+
+/// Barycentric weights of the point of triangle `abc` closest to `p`, from
+/// Ericson's Real-Time Collision Detection.
+fn closest_on_triangle(p: Vec3, a: Vec3, b: Vec3, c: Vec3) -> Vec3 {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+
+    let d1 = ab.dot(ap);
+    let d2 = ac.dot(ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return vec3(1.0, 0.0, 0.0);
+    }
+
+    let bp = p - b;
+    let d3 = ab.dot(bp);
+    let d4 = ac.dot(bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return vec3(0.0, 1.0, 0.0);
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let v = d1 / (d1 - d3);
+        return vec3(1.0 - v, v, 0.0);
+    }
+
+    let cp = p - c;
+    let d5 = ab.dot(cp);
+    let d6 = ac.dot(cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return vec3(0.0, 0.0, 1.0);
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let w = d2 / (d2 - d6);
+        return vec3(1.0 - w, 0.0, w);
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return vec3(0.0, 1.0 - w, w);
+    }
+
+    let denominator = va + vb + vc;
+
+    // degenerate triangles have no area to project onto
+    if denominator.abs() < f32::EPSILON {
+        return vec3(1.0, 0.0, 0.0);
+    }
+
+    let v = vb / denominator;
+    let w = vc / denominator;
+    vec3(1.0 - v - w, v, w)
+}
+
+/// Separating axis test between a triangle and a box, from Akenine-Möller.
+fn triangle_overlaps_aabb(corners: [Vec3; 3], aabb: &AxisAlignedBoundingBox) -> bool {
+    let center = aabb.center();
+    let half = aabb.size() * 0.5;
+    let [a, b, c] = corners.map(|corner| corner - center);
+
+    let separated = |axis: Vec3| {
+        let (pa, pb, pc) = (a.dot(axis), b.dot(axis), c.dot(axis));
+        let radius = half.x * axis.x.abs() + half.y * axis.y.abs() + half.z * axis.z.abs();
+
+        pa.min(pb).min(pc) > radius || pa.max(pb).max(pc) < -radius
+    };
+
+    let box_axes = [
+        vec3(1.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        vec3(0.0, 0.0, 1.0),
+    ];
+    let edges = [b - a, c - b, a - c];
+
+    if box_axes.iter().any(|&axis| separated(axis)) || separated(edges[0].cross(edges[1])) {
+        return false;
+    }
+
+    !box_axes
+        .iter()
+        .flat_map(|&axis| edges.iter().map(move |&edge| axis.cross(edge)))
+        .any(separated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sphere with a box beside it, so the tree has something to separate.
+    fn fixture() -> Bvh {
+        let sphere = CpuMesh::sphere(16);
+        let mut cube = CpuMesh::cube();
+        cube.transform(Mat4::from_translation(vec3(6.0, 0.5, 0.0)) * Mat4::from_scale(0.5))
+            .unwrap();
+
+        let mut positions = Vec::new();
+        let mut triangles = Vec::new();
+
+        for mesh in [&sphere, &cube] {
+            let offset = positions.len();
+            mesh.for_each_triangle(|a, b, c| triangles.push([a + offset, b + offset, c + offset]));
+            positions.extend(mesh.positions.to_f32());
+        }
+
+        Bvh::from_triangles(positions, triangles)
+    }
+
+    /// Rays from around the fixture towards points spread over and beside it.
+    fn rays() -> Vec<Ray> {
+        let origins = [
+            vec3(0.0, 0.0, 5.0),
+            vec3(-4.0, 2.0, 1.0),
+            vec3(9.0, -3.0, -2.0),
+            vec3(1.5, 0.2, 0.1),
+        ];
+
+        origins
+            .iter()
+            .flat_map(|&origin| {
+                (0..15).flat_map(move |i| {
+                    (0..15).map(move |j| {
+                        let target = vec3(i as f32 * 0.35 - 1.5, j as f32 * 0.25 - 1.5, 0.1);
+                        Ray::new(origin, target - origin)
+                    })
+                })
+            })
+            .collect()
+    }
+
+    /// The closest hit by testing every triangle.
+    fn brute_force(bvh: &Bvh, ray: &Ray) -> Option<TriangleHit> {
+        (0..bvh.triangles.len())
+            .filter_map(|triangle| bvh.intersect_triangle(ray, triangle))
+            .min_by(|a, b| a.t.total_cmp(&b.t))
+    }
+
+    fn assert_same_hits(bvh: &Bvh) {
+        let mut hits = 0;
+
+        for ray in rays() {
+            let expected = brute_force(bvh, &ray);
+
+            match (bvh.intersect(&ray), expected) {
+                (Some(hit), Some(expected)) => {
+                    // rays through shared edges may report either triangle
+                    assert!((hit.t - expected.t).abs() < 1e-5, "{ray:?}");
+                    hits += 1;
+                }
+                (None, None) => {}
+                (hit, expected) => panic!("{ray:?} hit {hit:?}, expected {expected:?}"),
+            }
+        }
+
+        // the rays are chosen so that both cases are tested
+        assert!(hits > 0 && hits < rays().len());
+    }
+
+    fn contains(outer: &AxisAlignedBoundingBox, inner: &AxisAlignedBoundingBox) -> bool {
+        (0..3).all(|axis| {
+            outer.min()[axis] <= inner.min()[axis] && inner.max()[axis] <= outer.max()[axis]
+        })
+    }
+
+    #[test]
+    fn build_places_every_triangle_in_one_leaf() {
+        let bvh = fixture();
+        let mut seen = vec![0; bvh.triangles.len()];
+
+        for node in bvh.nodes.iter() {
+            match node.kind {
+                NodeKind::Inner(left, right) => {
+                    assert!(contains(&node.bounds, &bvh.nodes[left].bounds));
+                    assert!(contains(&node.bounds, &bvh.nodes[right].bounds));
+                }
+                NodeKind::Leaf(start, end) => {
+                    assert!(end - start <= MAX_LEAF_SIZE);
+
+                    for &triangle in &bvh.order[start..end] {
+                        assert!(contains(&node.bounds, &bvh.triangle_bounds(&[triangle])));
+                        seen[triangle] += 1;
+                    }
+                }
+            }
+        }
+
+        assert!(seen.iter().all(|&count| count == 1));
+    }
+
+    #[test]
+    fn build_separates_distant_objects() {
+        let bvh = fixture();
+
+        let NodeKind::Inner(left, right) = bvh.nodes[0].kind else {
+            panic!("expected the root to be split");
+        };
+
+        // the sphere ends at x = 1 and the box starts at x = 5.5
+        let (left, right) = (&bvh.nodes[left].bounds, &bvh.nodes[right].bounds);
+        assert!(
+            left.max().x < 3.0 && right.min().x > 3.0 || right.max().x < 3.0 && left.min().x > 3.0
+        );
+    }
+
+    #[test]
+    fn intersect_matches_brute_force() {
+        assert_same_hits(&fixture());
+    }
+
+    #[test]
+    fn refit_matches_brute_force() {
+        let mut bvh = fixture();
+        let before = bvh.bounds();
+
+        let transformation = Mat4::from_translation(vec3(-0.5, 0.3, 0.2))
+            * Mat4::from_nonuniform_scale(1.5, 0.7, 1.0);
+        bvh.transform(transformation);
+
+        let expected_min = (transformation * before.min().extend(1.0)).truncate();
+        let expected_max = (transformation * before.max().extend(1.0)).truncate();
+        assert!((bvh.bounds().min() - expected_min).magnitude() < 1e-5);
+        assert!((bvh.bounds().max() - expected_max).magnitude() < 1e-5);
+
+        assert_same_hits(&bvh);
+    }
+
+    /// Query points inside, on and around both objects of the fixture.
+    fn points() -> Vec<Vec3> {
+        (0..12)
+            .flat_map(|i| {
+                (0..6).flat_map(move |j| {
+                    (0..4).map(move |k| {
+                        vec3(
+                            i as f32 * 0.8 - 2.0,
+                            j as f32 * 0.7 - 1.8,
+                            k as f32 * 0.9 - 1.3,
+                        )
+                    })
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn closest_point_matches_brute_force() {
+        let bvh = fixture();
+
+        for point in points() {
+            let closest = bvh.closest_point(point).unwrap();
+            let expected = (0..bvh.triangles.len())
+                .map(|triangle| {
+                    let [a, b, c] = bvh.corners(triangle);
+                    let weights = closest_on_triangle(point, a, b, c);
+                    (a * weights.x + b * weights.y + c * weights.z - point).magnitude()
+                })
+                .min_by(f32::total_cmp)
+                .unwrap();
+
+            // points equally far from several triangles may report any of them
+            assert!((closest.distance - expected).abs() < 1e-5, "{point:?}");
+
+            let [a, b, c] = bvh.corners(closest.triangle);
+            let weights = closest.barycentric;
+            assert!(
+                (a * weights.x + b * weights.y + c * weights.z - closest.point).magnitude() < 1e-5
+            );
+            assert!(((closest.point - point).magnitude() - closest.distance).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn closest_point_on_the_sphere() {
+        let closest = fixture().closest_point(vec3(0.0, 0.0, 5.0)).unwrap();
+
+        // the tessellated sphere lies within its unit radius
+        assert!(closest.distance >= 4.0 - 1e-5 && closest.distance < 4.05);
+        assert!(closest.point.z > 0.95);
+    }
+
+    #[test]
+    fn overlapping_matches_brute_force() {
+        let bvh = fixture();
+        let mut found = 0;
+
+        for (i, center) in points().into_iter().enumerate() {
+            let half = vec3(0.1, 0.2, 0.3) * (i % 5) as f32;
+            let aabb = AxisAlignedBoundingBox::new_with_positions(&[center - half, center + half]);
+
+            let mut triangles = bvh.overlapping(&aabb);
+            triangles.sort();
+
+            let expected: Vec<usize> = (0..bvh.triangles.len())
+                .filter(|&triangle| triangle_overlaps_aabb(bvh.corners(triangle), &aabb))
+                .collect();
+
+            assert_eq!(triangles, expected, "{aabb:?}");
+            found += triangles.len();
+        }
+
+        assert!(found > 0);
+    }
+
+    #[test]
+    fn overlapping_separates_distant_objects() {
+        let bvh = fixture();
+        let sphere_triangles = bvh.triangles.len() - 12;
+
+        // a box around the cube only, and a thin slab through both objects
+        let cube = AxisAlignedBoundingBox::new_with_positions(&[
+            vec3(5.0, -1.0, -1.0),
+            vec3(7.0, 1.0, 1.0),
+        ]);
+        let mut around_cube = bvh.overlapping(&cube);
+        around_cube.sort();
+        assert_eq!(
+            around_cube,
+            (sphere_triangles..bvh.triangles.len()).collect::<Vec<_>>()
+        );
+
+        let slab = AxisAlignedBoundingBox::new_with_positions(&[
+            vec3(-2.0, 0.49, -2.0),
+            vec3(8.0, 0.51, 2.0),
+        ]);
+        let slab = bvh.overlapping(&slab);
+        assert!(slab.iter().any(|&triangle| triangle < sphere_triangles));
+        assert!(slab.iter().any(|&triangle| triangle >= sphere_triangles));
+
+        assert!(bvh.overlapping(&AxisAlignedBoundingBox::EMPTY).is_empty());
+    }
+
+    #[test]
+    fn empty_mesh_has_no_hits() {
+        let bvh = Bvh::from_triangles(Vec::new(), Vec::new());
+
+        assert!(bvh.bounds().is_empty());
+        assert!(
+            bvh.intersect(&Ray::new(vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, 1.0)))
+                .is_none()
+        );
+        assert!(bvh.closest_point(vec3(0.0, 0.0, 0.0)).is_none());
+        assert!(
+            bvh.overlapping(&AxisAlignedBoundingBox::INFINITE)
+                .is_empty()
+        );
+    }
+}
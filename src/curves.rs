@@ -112,6 +112,11 @@ pub trait Curve {
         (i as f32 + segment_fraction) / (il - 1) as f32
     }
 
+    /// Returns the factor the tube radius is scaled by at the given interpolation factor.
+    fn get_radius_scale(&self, _t: f32) -> f32 {
+        1.0
+    }
+
     /// Returns a unit vector tangent for the given interpolation factor.
     fn get_tangent(&self, t: f32) -> Vec3 {
         let delta = 0.0001;
@@ -131,7 +136,7 @@ pub trait Curve {
     }
 
     /// Generates the Frenet Frames for the curve in 3D space.
-    fn compute_frenet_frames(&self, segments: usize, closed: bool) -> FrenetFrame {
+    fn compute_frenet_frames(&self, segments: usize, _closed: bool) -> FrenetFrame {
         let mut tangents = Vec::with_capacity(segments + 1);
         let mut normals = Vec::with_capacity(segments + 1);
         let mut binormals = Vec::with_capacity(segments + 1);
@@ -199,3 +204,254 @@ impl Curve for SineCurve {
         Some(Vec3::new(tx, ty, tz))
     }
 }
+
+/// A piecewise linear curve through a list of points.
+pub struct Polyline {
+    pub points: Vec<Vec3>,
+}
+
+impl Polyline {
+    pub fn new(points: Vec<Vec3>) -> Self {
+        Self { points }
+    }
+}
+
+impl Curve for Polyline {
+    fn get_point(&self, t: f32) -> Option<Vec3> {
+        let last = self.points.len().checked_sub(1)?;
+
+        if last == 0 {
+            return Some(self.points[0]);
+        }
+
+        let p = t.clamp(0.0, 1.0) * last as f32;
+        let i = (p.floor() as usize).min(last - 1);
+        let weight = p - i as f32;
+
+        Some(self.points[i] + (self.points[i + 1] - self.points[i]) * weight)
+    }
+}
+
+/// A uniform Catmull-Rom spline passing through all of its control points.
+pub struct CatmullRomCurve {
+    pub points: Vec<Vec3>,
+    pub closed: bool,
+    pub tension: f32,
+    /// Radius scale at each control point, interpolated linearly in between.
+    pub radius_scales: Option<Vec<f32>>,
+}
+
+impl CatmullRomCurve {
+    pub fn new(points: Vec<Vec3>, closed: bool) -> Self {
+        Self {
+            points,
+            closed,
+            tension: 0.5,
+            radius_scales: None,
+        }
+    }
+
+    /// Finds the segment `t` falls in, returning its index and the weight of its end.
+    fn segment(&self, t: f32) -> (usize, f32) {
+        let len = self.points.len();
+        let segments = if self.closed { len } else { len - 1 };
+        let p = t.clamp(0.0, 1.0) * segments as f32;
+        let i = (p.floor() as usize).min(segments - 1);
+
+        (i, p - i as f32)
+    }
+
+    /// Returns the control point at `i`, wrapping around for closed curves and
+    /// extrapolating past the ends for open ones.
+    fn control_point(&self, i: isize) -> Vec3 {
+        let len = self.points.len() as isize;
+
+        if self.closed {
+            return self.points[i.rem_euclid(len) as usize];
+        }
+
+        if i < 0 {
+            self.points[0] * 2.0 - self.points[1.min(len - 1) as usize]
+        } else if i >= len {
+            self.points[(len - 1) as usize] * 2.0 - self.points[(len - 2).max(0) as usize]
+        } else {
+            self.points[i as usize]
+        }
+    }
+}
+
+impl Curve for CatmullRomCurve {
+    fn get_point(&self, t: f32) -> Option<Vec3> {
+        let len = self.points.len();
+
+        if len == 0 {
+            return None;
+        }
+
+        if len == 1 {
+            return Some(self.points[0]);
+        }
+
+        let (i, weight) = self.segment(t);
+        let i = i as isize;
+
+        let p0 = self.control_point(i - 1);
+        let p1 = self.control_point(i);
+        let p2 = self.control_point(i + 1);
+        let p3 = self.control_point(i + 2);
+
+        // cubic hermite segment with catmull-rom tangents
+        let m1 = (p2 - p0) * self.tension;
+        let m2 = (p3 - p1) * self.tension;
+
+        let w2 = weight * weight;
+        let w3 = w2 * weight;
+
+        Some(
+            p1 * (2.0 * w3 - 3.0 * w2 + 1.0)
+                + m1 * (w3 - 2.0 * w2 + weight)
+                + p2 * (-2.0 * w3 + 3.0 * w2)
+                + m2 * (w3 - w2),
+        )
+    }
+
+    fn get_radius_scale(&self, t: f32) -> f32 {
+        let Some(scales) = &self.radius_scales else {
+            return 1.0;
+        };
+
+        if scales.len() != self.points.len() || scales.len() < 2 {
+            return scales.first().copied().unwrap_or(1.0);
+        }
+
+        let (i, weight) = self.segment(t);
+        let next = (i + 1) % scales.len();
+
+        scales[i] + (scales[next] - scales[i]) * weight
+    }
+}
+
+/// A (possibly rational) B-spline curve evaluated with de Boor's algorithm.
+pub struct BSplineCurve {
+    pub degree: usize,
+    pub control_points: Vec<Vec3>,
+    pub weights: Vec<f32>,
+    pub knots: Vec<f32>,
+    /// Parameter range mapped onto `t` in `[0, 1]`.
+    pub range: (f32, f32),
+}
+
+impl BSplineCurve {
+    /// Creates a non-rational B-spline, returns `None` if the knot vector does not
+    /// have `control_points.len() + degree + 1` entries.
+    pub fn new(degree: usize, control_points: Vec<Vec3>, knots: Vec<f32>) -> Option<Self> {
+        let weights = vec![1.0; control_points.len()];
+        Self::new_rational(degree, control_points, weights, knots)
+    }
+
+    pub fn new_rational(
+        degree: usize,
+        control_points: Vec<Vec3>,
+        weights: Vec<f32>,
+        knots: Vec<f32>,
+    ) -> Option<Self> {
+        if degree == 0
+            || control_points.len() <= degree
+            || weights.len() != control_points.len()
+            || knots.len() != control_points.len() + degree + 1
+        {
+            return None;
+        }
+
+        let range = (knots[degree], knots[control_points.len()]);
+
+        Some(Self {
+            degree,
+            control_points,
+            weights,
+            knots,
+            range,
+        })
+    }
+
+    /// Creates a B-spline with a clamped uniform knot vector, so the curve starts
+    /// and ends at the first and last control points.
+    pub fn clamped(degree: usize, control_points: Vec<Vec3>) -> Option<Self> {
+        let n = control_points.len();
+
+        if degree == 0 || n <= degree {
+            return None;
+        }
+
+        let spans = n - degree;
+        let mut knots = vec![0.0; degree + 1];
+        knots.extend((1..spans).map(|i| i as f32 / spans as f32));
+        knots.extend(std::iter::repeat_n(1.0, degree + 1));
+
+        Self::new(degree, control_points, knots)
+    }
+
+    /// Creates a single Bézier curve of degree `control_points.len() - 1`.
+    pub fn bezier(control_points: Vec<Vec3>) -> Option<Self> {
+        let n = control_points.len();
+
+        if n < 2 {
+            return None;
+        }
+
+        let mut knots = vec![0.0; n];
+        knots.extend(std::iter::repeat_n(1.0, n));
+
+        Self::new(n - 1, control_points, knots)
+    }
+
+    /// Returns the index of the knot span containing `u`.
+    fn find_span(&self, u: f32) -> usize {
+        let last = self.control_points.len() - 1;
+
+        if u >= self.knots[last + 1] {
+            return last;
+        }
+
+        (self.degree..=last)
+            .rev()
+            .find(|&k| self.knots[k] <= u)
+            .unwrap_or(self.degree)
+    }
+}
+
+impl Curve for BSplineCurve {
+    fn get_point(&self, t: f32) -> Option<Vec3> {
+        let p = self.degree;
+        let u = self.range.0 + t.clamp(0.0, 1.0) * (self.range.1 - self.range.0);
+        let k = self.find_span(u);
+
+        // homogeneous control points of the affected span
+        let mut d: Vec<(Vec3, f32)> = (0..=p)
+            .map(|j| {
+                let i = j + k - p;
+                (self.control_points[i] * self.weights[i], self.weights[i])
+            })
+            .collect();
+
+        for r in 1..=p {
+            for j in (r..=p).rev() {
+                let left = self.knots[j + k - p];
+                let right = self.knots[j + 1 + k - r];
+                let alpha = if right > left {
+                    (u - left) / (right - left)
+                } else {
+                    0.0
+                };
+
+                d[j] = (
+                    d[j - 1].0 * (1.0 - alpha) + d[j].0 * alpha,
+                    d[j - 1].1 * (1.0 - alpha) + d[j].1 * alpha,
+                );
+            }
+        }
+
+        let (point, weight) = d[p];
+        (weight != 0.0).then(|| point / weight)
+    }
+}
@@ -0,0 +1,452 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use three_d::{CpuMaterial, CpuMesh, InnerSpace, Mat4, SquareMatrix, Vec3};
+
+use crate::glb::{GlbNode, write_glb};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Obj,
+    StlBinary,
+    StlAscii,
+    Ply,
+    Glb,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 5] = [
+        ExportFormat::Obj,
+        ExportFormat::StlBinary,
+        ExportFormat::StlAscii,
+        ExportFormat::Ply,
+        ExportFormat::Glb,
+    ];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Obj => "obj",
+            ExportFormat::StlBinary | ExportFormat::StlAscii => "stl",
+            ExportFormat::Ply => "ply",
+            ExportFormat::Glb => "glb",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Obj => "Wavefront OBJ",
+            ExportFormat::StlBinary => "STL (binary)",
+            ExportFormat::StlAscii => "STL (ASCII)",
+            ExportFormat::Ply => "PLY",
+            ExportFormat::Glb => "glTF binary (GLB)",
+        }
+    }
+}
+
+/// Writes `mesh` to `path` in the given format. OBJ files get a material
+/// library with the same file stem written next to them.
+pub fn export_mesh(
+    path: &Path,
+    mesh: &CpuMesh,
+    material: &CpuMaterial,
+    format: ExportFormat,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    match format {
+        ExportFormat::Obj => {
+            let mtl_path = path.with_extension("mtl");
+            let mtl_name = mtl_path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("material.mtl");
+
+            write_obj(&mut writer, mesh, Some(mtl_name), &material.name)?;
+
+            let mut mtl_writer = BufWriter::new(File::create(&mtl_path)?);
+            write_mtl(&mut mtl_writer, material)?;
+            mtl_writer.flush()?;
+        }
+        ExportFormat::StlBinary => write_stl_binary(&mut writer, mesh)?,
+        ExportFormat::StlAscii => write_stl_ascii(&mut writer, mesh)?,
+        ExportFormat::Ply => write_ply(&mut writer, mesh)?,
+        ExportFormat::Glb => write_glb(
+            &mut writer,
+            &[GlbNode {
+                name: "mesh",
+                mesh,
+                material,
+                transformation: Mat4::identity(),
+            }],
+        )?,
+    }
+
+    writer.flush()
+}
+
+/// Writes positions, and normals and texture coordinates when present, as a Wavefront OBJ.
+pub fn write_obj(
+    writer: &mut impl Write,
+    mesh: &CpuMesh,
+    material_library: Option<&str>,
+    material_name: &str,
+) -> io::Result<()> {
+    let material_name = if material_name.is_empty() {
+        "default"
+    } else {
+        material_name
+    };
+
+    if let Some(material_library) = material_library {
+        writeln!(writer, "mtllib {material_library}")?;
+    }
+
+    writeln!(writer, "o mesh")?;
+
+    for p in mesh.positions.to_f32() {
+        writeln!(writer, "v {} {} {}", p.x, p.y, p.z)?;
+    }
+
+    if let Some(uvs) = &mesh.uvs {
+        // the OBJ loader flips v, so flip it back
+        for uv in uvs {
+            writeln!(writer, "vt {} {}", uv.x, 1.0 - uv.y)?;
+        }
+    }
+
+    if let Some(normals) = &mesh.normals {
+        for n in normals {
+            writeln!(writer, "vn {} {} {}", n.x, n.y, n.z)?;
+        }
+    }
+
+    if material_library.is_some() {
+        writeln!(writer, "usemtl {material_name}")?;
+    }
+
+    let has_uvs = mesh.uvs.is_some();
+    let has_normals = mesh.normals.is_some();
+
+    for [a, b, c] in triangles(mesh) {
+        let vertex = |i: usize| match (has_uvs, has_normals) {
+            (true, true) => format!("{0}/{0}/{0}", i + 1),
+            (true, false) => format!("{0}/{0}", i + 1),
+            (false, true) => format!("{0}//{0}", i + 1),
+            (false, false) => format!("{}", i + 1),
+        };
+
+        writeln!(writer, "f {} {} {}", vertex(a), vertex(b), vertex(c))?;
+    }
+
+    Ok(())
+}
+
+/// Writes a single material in the MTL format, mapping the PBR parameters back
+/// the same way the OBJ loader maps them in.
+pub fn write_mtl(writer: &mut impl Write, material: &CpuMaterial) -> io::Result<()> {
+    let name = if material.name.is_empty() {
+        "default"
+    } else {
+        &material.name
+    };
+    let albedo: [f32; 4] = material.albedo.into();
+    let roughness = material.roughness.max(0.01);
+
+    // the loader expects the statements in exactly this order
+    writeln!(writer, "newmtl {name}")?;
+    writeln!(writer, "Ns {}", 1.999 / (roughness * roughness))?;
+    writeln!(writer, "Ka 0 0 0")?;
+    writeln!(writer, "Kd {} {} {}", albedo[0], albedo[1], albedo[2])?;
+    writeln!(writer, "Ks {0} {0} {0}", material.metallic.clamp(0.0, 1.0))?;
+    writeln!(writer, "d {}", albedo[3])?;
+    writeln!(writer, "illum 2")
+}
+
+pub fn write_stl_ascii(writer: &mut impl Write, mesh: &CpuMesh) -> io::Result<()> {
+    let positions = mesh.positions.to_f32();
+
+    writeln!(writer, "solid mesh")?;
+
+    for [a, b, c] in triangles(mesh) {
+        let (p1, p2, p3) = (positions[a], positions[b], positions[c]);
+        let n = face_normal(p1, p2, p3);
+
+        writeln!(writer, "  facet normal {} {} {}", n.x, n.y, n.z)?;
+        writeln!(writer, "    outer loop")?;
+        for p in [p1, p2, p3] {
+            writeln!(writer, "      vertex {} {} {}", p.x, p.y, p.z)?;
+        }
+        writeln!(writer, "    endloop")?;
+        writeln!(writer, "  endfacet")?;
+    }
+
+    writeln!(writer, "endsolid mesh")
+}
+
+pub fn write_stl_binary(writer: &mut impl Write, mesh: &CpuMesh) -> io::Result<()> {
+    let positions = mesh.positions.to_f32();
+    let triangles = triangles(mesh);
+
+    let mut header = [0u8; 80];
+    let title = b"rene binary stl";
+    header[..title.len()].copy_from_slice(title);
+
+    writer.write_all(&header)?;
+    writer.write_all(&(triangles.len() as u32).to_le_bytes())?;
+
+    for [a, b, c] in triangles {
+        let (p1, p2, p3) = (positions[a], positions[b], positions[c]);
+
+        for v in [face_normal(p1, p2, p3), p1, p2, p3] {
+            write_vec3_le(writer, v)?;
+        }
+
+        // attribute byte count
+        writer.write_all(&0u16.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Writes a binary little endian PLY with normals, texture coordinates and colors when present.
+pub fn write_ply(writer: &mut impl Write, mesh: &CpuMesh) -> io::Result<()> {
+    let positions = mesh.positions.to_f32();
+    let triangles = triangles(mesh);
+
+    writeln!(writer, "ply")?;
+    writeln!(writer, "format binary_little_endian 1.0")?;
+    writeln!(writer, "element vertex {}", positions.len())?;
+    writeln!(writer, "property float x")?;
+    writeln!(writer, "property float y")?;
+    writeln!(writer, "property float z")?;
+
+    if mesh.normals.is_some() {
+        writeln!(writer, "property float nx")?;
+        writeln!(writer, "property float ny")?;
+        writeln!(writer, "property float nz")?;
+    }
+
+    if mesh.uvs.is_some() {
+        writeln!(writer, "property float s")?;
+        writeln!(writer, "property float t")?;
+    }
+
+    if mesh.colors.is_some() {
+        writeln!(writer, "property uchar red")?;
+        writeln!(writer, "property uchar green")?;
+        writeln!(writer, "property uchar blue")?;
+        writeln!(writer, "property uchar alpha")?;
+    }
+
+    writeln!(writer, "element face {}", triangles.len())?;
+    writeln!(writer, "property list uchar uint vertex_indices")?;
+    writeln!(writer, "end_header")?;
+
+    for (i, p) in positions.iter().enumerate() {
+        write_vec3_le(writer, *p)?;
+
+        if let Some(normals) = &mesh.normals {
+            write_vec3_le(writer, normals[i])?;
+        }
+
+        if let Some(uvs) = &mesh.uvs {
+            writer.write_all(&uvs[i].x.to_le_bytes())?;
+            writer.write_all(&uvs[i].y.to_le_bytes())?;
+        }
+
+        if let Some(colors) = &mesh.colors {
+            let color = colors[i];
+            writer.write_all(&[color.r, color.g, color.b, color.a])?;
+        }
+    }
+
+    for triangle in triangles {
+        writer.write_all(&[3u8])?;
+        for i in triangle {
+            writer.write_all(&(i as u32).to_le_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Collects the vertex indices of every triangle, for indexed and non-indexed meshes alike.
+fn triangles(mesh: &CpuMesh) -> Vec<[usize; 3]> {
+    let mut triangles = Vec::with_capacity(mesh.triangle_count());
+    mesh.for_each_triangle(|a, b, c| triangles.push([a, b, c]));
+    triangles
+}
+
+fn face_normal(p1: Vec3, p2: Vec3, p3: Vec3) -> Vec3 {
+    let normal = (p2 - p1).cross(p3 - p1);
+
+    if normal.magnitude2() > 0.0 {
+        normal.normalize()
+    } else {
+        normal
+    }
+}
+
+fn write_vec3_le(writer: &mut impl Write, v: Vec3) -> io::Result<()> {
+    writer.write_all(&v.x.to_le_bytes())?;
+    writer.write_all(&v.y.to_le_bytes())?;
+    writer.write_all(&v.z.to_le_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use three_d::{Indices, Positions, vec2, vec3};
+
+    /// A unit square of two triangles, indexed, with normals.
+    fn square() -> CpuMesh {
+        CpuMesh {
+            positions: Positions::F32(vec![
+                vec3(0.0, 0.0, 0.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(1.0, 1.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+            ]),
+            indices: Indices::U32(vec![0, 1, 2, 0, 2, 3]),
+            normals: Some(vec![vec3(0.0, 0.0, 1.0); 4]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn obj_faces_use_one_based_indices() {
+        let mut bytes = Vec::new();
+        write_obj(&mut bytes, &square(), None, "").unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines.iter().filter(|line| line.starts_with("v ")).count(),
+            4
+        );
+        assert_eq!(
+            lines.iter().filter(|line| line.starts_with("vn ")).count(),
+            4
+        );
+        assert!(!text.contains("mtllib") && !text.contains("usemtl"));
+
+        let faces: Vec<&str> = lines
+            .into_iter()
+            .filter(|line| line.starts_with("f "))
+            .collect();
+        assert_eq!(faces, ["f 1//1 2//2 3//3", "f 1//1 3//3 4//4"]);
+    }
+
+    #[test]
+    fn obj_faces_with_texture_coordinates() {
+        let mut mesh = square();
+        mesh.uvs = Some(vec![vec2(0.0, 0.0); 4]);
+
+        let mut bytes = Vec::new();
+        write_obj(&mut bytes, &mesh, Some("square.mtl"), "paint").unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+
+        assert!(text.starts_with("mtllib square.mtl\n"));
+        assert!(text.contains("usemtl paint\n"));
+        assert!(text.contains("f 1/1/1 2/2/2 3/3/3\n"));
+    }
+
+    #[test]
+    fn binary_stl_layout() {
+        let mesh = CpuMesh::cube();
+        let count = mesh.triangle_count();
+
+        let mut bytes = Vec::new();
+        write_stl_binary(&mut bytes, &mesh).unwrap();
+
+        assert_eq!(bytes.len(), 84 + 50 * count);
+        assert!(bytes[..80].starts_with(b"rene binary stl"));
+        assert_eq!(
+            u32::from_le_bytes(bytes[80..84].try_into().unwrap()) as usize,
+            count
+        );
+
+        // the first facet normal of an indexed mesh
+        let mut bytes = Vec::new();
+        write_stl_binary(&mut bytes, &square()).unwrap();
+
+        assert_eq!(bytes.len(), 84 + 50 * 2);
+        let normal_z = f32::from_le_bytes(bytes[92..96].try_into().unwrap());
+        assert_eq!(normal_z, 1.0);
+    }
+
+    #[test]
+    fn ascii_stl_has_a_facet_per_triangle() {
+        let mut bytes = Vec::new();
+        write_stl_ascii(&mut bytes, &square()).unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+
+        assert!(text.starts_with("solid mesh\n"));
+        assert!(text.ends_with("endsolid mesh\n"));
+        assert_eq!(text.matches("facet normal 0 0 1").count(), 2);
+        assert_eq!(text.matches("vertex ").count(), 6);
+    }
+
+    /// The element counts from a PLY header and the body after it.
+    fn ply_parts(bytes: &[u8]) -> (usize, usize, &[u8]) {
+        let end = b"end_header\n";
+        let split = bytes
+            .windows(end.len())
+            .position(|window| window == end)
+            .unwrap()
+            + end.len();
+        let header = std::str::from_utf8(&bytes[..split]).unwrap();
+
+        let count = |element: &str| {
+            header
+                .lines()
+                .find_map(|line| line.strip_prefix(element))
+                .unwrap()
+                .parse::<usize>()
+                .unwrap()
+        };
+
+        (
+            count("element vertex "),
+            count("element face "),
+            &bytes[split..],
+        )
+    }
+
+    #[test]
+    fn ply_header_counts_match_body() {
+        let mut bytes = Vec::new();
+        write_ply(&mut bytes, &square()).unwrap();
+        let (vertices, faces, body) = ply_parts(&bytes);
+
+        assert_eq!((vertices, faces), (4, 2));
+        // positions and normals, then a count byte and three indices per face
+        assert_eq!(body.len(), vertices * 24 + faces * 13);
+
+        let last_face = &body[body.len() - 13..];
+        assert_eq!(last_face[0], 3);
+        let indices: Vec<u32> = last_face[1..]
+            .chunks(4)
+            .map(|index| u32::from_le_bytes(index.try_into().unwrap()))
+            .collect();
+        assert_eq!(indices, [0, 2, 3]);
+    }
+
+    #[test]
+    fn ply_body_grows_with_optional_attributes() {
+        let mut mesh = square();
+        mesh.normals = None;
+        mesh.uvs = Some(vec![vec2(0.0, 0.0); 4]);
+        mesh.colors = Some(vec![three_d::Srgba::WHITE; 4]);
+
+        let mut bytes = Vec::new();
+        write_ply(&mut bytes, &mesh).unwrap();
+        let (vertices, faces, body) = ply_parts(&bytes);
+
+        // positions, texture coordinates and rgba colors
+        assert_eq!(body.len(), vertices * (12 + 8 + 4) + faces * 13);
+    }
+}
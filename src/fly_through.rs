@@ -0,0 +1,198 @@
+use three_d::{InnerSpace, Vec3, vec3};
+
+use crate::{curves::Curve, view::CameraPose};
+
+/// Samples taken along the curve, the camera is interpolated between them.
+const SEGMENTS: usize = 200;
+
+/// A camera riding along a curve, looking down its tangent with the curve
+/// normal as up.
+pub struct FlyThrough {
+    points: Vec<Vec3>,
+    tangents: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    length: f32,
+    /// Position along the curve by arc length, from 0 to 1.
+    pub u: f32,
+    /// Units per second.
+    pub speed: f32,
+    pub playing: bool,
+    /// Starts over at the end instead of stopping.
+    pub looping: bool,
+}
+
+impl FlyThrough {
+    pub fn new(curve: &dyn Curve, closed: bool) -> Self {
+        let mut fly_through = Self {
+            points: Vec::new(),
+            tangents: Vec::new(),
+            normals: Vec::new(),
+            length: 0.0,
+            u: 0.0,
+            speed: 0.5,
+            playing: false,
+            looping: true,
+        };
+        fly_through.set_curve(curve, closed);
+        fly_through
+    }
+
+    /// Follows another curve from the same `u`.
+    ///
+    /// No curve overrides [`Curve::get_point_at`], which maps `u` through the
+    /// cumulative chord lengths of [`Curve::get_lengths`], so the samples are
+    /// evenly spaced by arc length whatever the parameterization of the curve.
+    pub fn set_curve(&mut self, curve: &dyn Curve, closed: bool) {
+        let frame = curve.compute_frenet_frames(SEGMENTS, closed);
+
+        self.points = (0..=SEGMENTS)
+            .map(|i| {
+                curve
+                    .get_point_at(i as f32 / SEGMENTS as f32)
+                    .unwrap_or(vec3(0.0, 0.0, 0.0))
+            })
+            .collect();
+        self.tangents = frame.tangents;
+        self.normals = frame.normals;
+        self.length = curve.get_length();
+    }
+
+    /// Moves along the curve by `seconds` of travel when playing.
+    pub fn update(&mut self, seconds: f32) {
+        if !self.playing || self.length <= f32::EPSILON {
+            return;
+        }
+
+        self.u += self.speed * seconds / self.length;
+
+        if self.u >= 1.0 {
+            if self.looping {
+                self.u = self.u.fract();
+            } else {
+                self.u = 1.0;
+                self.playing = false;
+            }
+        }
+    }
+
+    /// The camera at `u`, looking one unit ahead.
+    pub fn pose(&self) -> CameraPose {
+        let position = self.u.clamp(0.0, 1.0) * SEGMENTS as f32;
+        let index = (position.floor() as usize).min(SEGMENTS - 1);
+        let fraction = position - index as f32;
+
+        let lerp = |values: &[Vec3]| values[index] + (values[index + 1] - values[index]) * fraction;
+
+        let point = lerp(&self.points);
+        let tangent = lerp(&self.tangents);
+        let tangent = if tangent.magnitude2() > f32::EPSILON {
+            tangent.normalize()
+        } else {
+            self.tangents[index]
+        };
+
+        // the normal is not always perpendicular to the tangent, and falls
+        // back to world up where it runs along it
+        let normal = lerp(&self.normals);
+        let up = [normal, vec3(0.0, 1.0, 0.0), vec3(0.0, 0.0, 1.0)]
+            .into_iter()
+            .map(|up| up - tangent * up.dot(tangent))
+            .find(|up| up.magnitude2() > 1e-6)
+            .map_or(vec3(0.0, 1.0, 0.0), InnerSpace::normalize);
+
+        CameraPose {
+            position: point,
+            target: point + tangent,
+            up,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curves::{BSplineCurve, CatmullRomCurve};
+
+    /// Control points three times further apart in some places than in others,
+    /// but not so uneven that the spline doubles back on itself.
+    fn uneven_points() -> Vec<Vec3> {
+        vec![
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.3, 0.1, 0.0),
+            vec3(1.2, 0.4, 0.1),
+            vec3(1.5, 0.3, 0.2),
+            vec3(2.5, 0.0, 0.0),
+        ]
+    }
+
+    fn assert_evenly_spaced(fly_through: &FlyThrough) {
+        let spacing: Vec<f32> = fly_through
+            .points
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).magnitude())
+            .collect();
+        let mean = fly_through.length / SEGMENTS as f32;
+
+        for (index, &distance) in spacing.iter().enumerate() {
+            assert!(
+                (distance - mean).abs() < mean * 0.05,
+                "sample {index} is {distance} from the next, expected {mean}"
+            );
+        }
+    }
+
+    #[test]
+    fn catmull_rom_samples_are_evenly_spaced() {
+        let curve = CatmullRomCurve::new(uneven_points(), false);
+
+        assert_evenly_spaced(&FlyThrough::new(&curve, false));
+    }
+
+    #[test]
+    fn b_spline_samples_are_evenly_spaced() {
+        let curve = BSplineCurve::clamped(3, uneven_points()).unwrap();
+
+        assert_evenly_spaced(&FlyThrough::new(&curve, false));
+    }
+
+    #[test]
+    fn update_travels_speed_times_seconds() {
+        let curve = CatmullRomCurve::new(uneven_points(), false);
+        let mut fly_through = FlyThrough::new(&curve, false);
+        fly_through.speed = 1.0;
+        fly_through.playing = true;
+
+        fly_through.update(1.5);
+
+        assert!((fly_through.u - 1.5 / fly_through.length).abs() < 1e-6);
+
+        // measured along the samples the camera follows
+        let travelled: f32 = fly_through
+            .points
+            .windows(2)
+            .take((fly_through.u * SEGMENTS as f32).round() as usize)
+            .map(|pair| (pair[1] - pair[0]).magnitude())
+            .sum();
+        assert!((travelled - 1.5).abs() < 0.05, "travelled {travelled}");
+    }
+
+    #[test]
+    fn update_stops_or_loops_at_the_end() {
+        let curve = CatmullRomCurve::new(uneven_points(), false);
+        let mut fly_through = FlyThrough::new(&curve, false);
+        let seconds = fly_through.length * 1.25 / fly_through.speed;
+
+        fly_through.update(seconds);
+        assert_eq!(fly_through.u, 0.0);
+
+        fly_through.playing = true;
+        fly_through.update(seconds);
+        assert!((fly_through.u - 0.25).abs() < 1e-4);
+        assert!(fly_through.playing);
+
+        fly_through.looping = false;
+        fly_through.update(seconds);
+        assert_eq!(fly_through.u, 1.0);
+        assert!(!fly_through.playing);
+    }
+}
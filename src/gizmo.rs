@@ -0,0 +1,480 @@
+use three_d::{
+    Camera, ColorMaterial, Context, CpuMesh, Deg, DepthTest, Event, Gm, InnerSpace, InstancedMesh,
+    Mat3, Mat4, Mesh, MouseButton, Object, Rad, Srgba, Vec3, rotation_matrix_from_dir_to_dir, vec3,
+};
+
+use crate::{model::ModelTransform, ray::Ray, wireframe::edge_transform};
+
+/// Screen size of the gizmo as a fraction of its distance to the camera.
+const SCREEN_SIZE: f32 = 0.15;
+/// How close, relative to the gizmo size, the cursor has to be to grab a handle.
+const GRAB_DISTANCE: f32 = 0.08;
+/// Offset and half size of the plane handles, relative to the gizmo size.
+const PLANE_OFFSET: f32 = 0.3;
+const PLANE_SIZE: f32 = 0.1;
+
+const TRANSLATE_STEP: f32 = 0.1;
+const ROTATE_STEP: f32 = 15.0;
+const SCALE_STEP: f32 = 0.1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GizmoMode {
+    Translate,
+    Rotate,
+    Scale,
+}
+
+impl GizmoMode {
+    pub const ALL: [GizmoMode; 3] = [GizmoMode::Translate, GizmoMode::Rotate, GizmoMode::Scale];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GizmoMode::Translate => "Translate",
+            GizmoMode::Rotate => "Rotate",
+            GizmoMode::Scale => "Scale",
+        }
+    }
+}
+
+/// The constraint of a handle, a single axis or the plane spanned by two of them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GizmoHandle {
+    Axis(usize),
+    Plane(usize, usize),
+}
+
+const PLANES: [(usize, usize); 3] = [(0, 1), (1, 2), (2, 0)];
+
+struct Drag {
+    handle: GizmoHandle,
+    start: ModelTransform,
+    /// Where the constraint was grabbed, an axis parameter or a point on a plane.
+    grab: Vec3,
+}
+
+/// Translate, rotate and scale handles drawn over the scene, which edit a
+/// [`ModelTransform`] when dragged with the left mouse button.
+///
+/// Translation uses the world axes, scaling the axes of the rotated object and
+/// rotation the gimbal axes of its euler angles, so every handle edits exactly one
+/// component. Snapping is on while `snapping` is set or control is held.
+pub struct Gizmo {
+    pub mode: GizmoMode,
+    pub snapping: bool,
+    /// Set for targets that only have a position, such as points, which are
+    /// then translated whatever the mode.
+    pub translate_only: bool,
+    hovered: Option<GizmoHandle>,
+    drag: Option<Drag>,
+    arrows: [Gm<Mesh, ColorMaterial>; 3],
+    scale_handles: [Gm<Mesh, ColorMaterial>; 3],
+    planes: [Gm<Mesh, ColorMaterial>; 3],
+    rings: [Gm<InstancedMesh, ColorMaterial>; 3],
+}
+
+impl Gizmo {
+    pub fn new(context: &Context) -> Self {
+        let mut arrow = CpuMesh::arrow(0.8, 0.5, 16);
+        arrow
+            .transform(Mat4::from_nonuniform_scale(1.0, 0.06, 0.06))
+            .unwrap();
+
+        // a thin stick with a cube at its end
+        let mut stick = CpuMesh::cylinder(8);
+        stick
+            .transform(Mat4::from_nonuniform_scale(0.9, 0.02, 0.02))
+            .unwrap();
+        let mut cube = CpuMesh::cube();
+        cube.transform(Mat4::from_translation(vec3(0.93, 0.0, 0.0)) * Mat4::from_scale(0.07))
+            .unwrap();
+        let scale_handle = merge(&stick, &cube);
+
+        let mut square = CpuMesh::square();
+        square.transform(Mat4::from_scale(PLANE_SIZE)).unwrap();
+
+        // a unit circle around the x axis made of short cylinders
+        let mut segment = CpuMesh::cylinder(6);
+        segment
+            .transform(Mat4::from_nonuniform_scale(1.0, 0.015, 0.015))
+            .unwrap();
+        let circle = (0..64)
+            .map(|i| {
+                let angle = std::f32::consts::TAU * i as f32 / 64.0;
+                vec3(0.0, angle.cos(), angle.sin())
+            })
+            .collect::<Vec<_>>();
+        let ring = three_d::Instances {
+            transformations: (0..circle.len())
+                .map(|i| edge_transform(circle[i], circle[(i + 1) % circle.len()]))
+                .collect(),
+            ..Default::default()
+        };
+
+        Self {
+            mode: GizmoMode::Translate,
+            snapping: false,
+            translate_only: false,
+            hovered: None,
+            drag: None,
+            arrows: [0, 1, 2].map(|_| Gm::new(Mesh::new(context, &arrow), overlay())),
+            scale_handles: [0, 1, 2].map(|_| Gm::new(Mesh::new(context, &scale_handle), overlay())),
+            planes: [0, 1, 2].map(|_| Gm::new(Mesh::new(context, &square), overlay())),
+            rings: [0, 1, 2]
+                .map(|_| Gm::new(InstancedMesh::new(context, &ring, &segment), overlay())),
+        }
+    }
+
+    /// The mode the handles follow, translate for targets with only a position.
+    pub fn active_mode(&self) -> GizmoMode {
+        if self.translate_only {
+            GizmoMode::Translate
+        } else {
+            self.mode
+        }
+    }
+
+    /// Hovers and drags handles, editing `transform`. Mouse events used by the
+    /// gizmo are marked as handled, so camera controls should run after this.
+    /// Returns whether `transform` changed.
+    pub fn handle_events(
+        &mut self,
+        camera: &Camera,
+        events: &mut [Event],
+        transform: &mut ModelTransform,
+    ) -> bool {
+        let mut changed = false;
+
+        for event in events.iter_mut() {
+            match event {
+                Event::MousePress {
+                    button: MouseButton::Left,
+                    position,
+                    handled,
+                    ..
+                } if !*handled => {
+                    let ray = Ray::from_pixel(camera, *position);
+
+                    if let Some(handle) = self.hit(camera, &ray, transform)
+                        && let Some(grab) = self.grab(camera, &ray, transform, handle)
+                    {
+                        self.drag = Some(Drag {
+                            handle,
+                            start: *transform,
+                            grab,
+                        });
+                        *handled = true;
+                    }
+                }
+                Event::MouseMotion {
+                    position,
+                    modifiers,
+                    handled,
+                    ..
+                } if !*handled => {
+                    let ray = Ray::from_pixel(camera, *position);
+
+                    if let Some(drag) = &self.drag {
+                        let snap = self.snapping || modifiers.ctrl;
+
+                        if let Some(dragged) = self.dragged(camera, &ray, drag, snap)
+                            && dragged != *transform
+                        {
+                            *transform = dragged;
+                            changed = true;
+                        }
+
+                        *handled = true;
+                    } else {
+                        self.hovered = self.hit(camera, &ray, transform);
+                    }
+                }
+                Event::MouseRelease {
+                    button: MouseButton::Left,
+                    handled,
+                    ..
+                } if self.drag.is_some() => {
+                    self.drag = None;
+                    *handled = true;
+                }
+                _ => {}
+            }
+        }
+
+        changed
+    }
+
+    /// The handles of the current mode, placed on `transform`.
+    pub fn objects(&mut self, camera: &Camera, transform: &ModelTransform) -> Vec<&dyn Object> {
+        let center = transform.position;
+        let size = size(camera, center);
+        let axes = axes(self.active_mode(), transform);
+        let active = self.drag.as_ref().map(|drag| drag.handle).or(self.hovered);
+
+        let color = |handle: GizmoHandle| -> Srgba {
+            if active == Some(handle) {
+                return Srgba::new_opaque(255, 220, 0);
+            }
+
+            match handle {
+                GizmoHandle::Axis(0) => Srgba::new_opaque(220, 50, 50),
+                GizmoHandle::Axis(1) => Srgba::new_opaque(60, 190, 60),
+                GizmoHandle::Axis(_) => Srgba::new_opaque(60, 90, 230),
+                GizmoHandle::Plane(..) => Srgba::new_opaque(160, 160, 160),
+            }
+        };
+
+        let placement = |axis: Vec3| {
+            Mat4::from_translation(center)
+                * Mat4::from_scale(size)
+                * rotation_matrix_from_dir_to_dir(vec3(1.0, 0.0, 0.0), axis.normalize())
+        };
+
+        let mut objects: Vec<&dyn Object> = Vec::new();
+
+        match self.active_mode() {
+            GizmoMode::Translate | GizmoMode::Scale => {
+                let sticks = if self.active_mode() == GizmoMode::Translate {
+                    &mut self.arrows
+                } else {
+                    &mut self.scale_handles
+                };
+
+                for (i, stick) in sticks.iter_mut().enumerate() {
+                    stick.set_transformation(placement(axes[i]));
+                    stick.material.color = color(GizmoHandle::Axis(i));
+                }
+
+                for (plane, &(a, b)) in self.planes.iter_mut().zip(PLANES.iter()) {
+                    let (u, v) = (axes[a].normalize(), axes[b].normalize());
+                    let orientation = Mat3::from_cols(u, v, u.cross(v));
+
+                    plane.set_transformation(
+                        Mat4::from_translation(center + (u + v) * PLANE_OFFSET * size)
+                            * Mat4::from_scale(size)
+                            * Mat4::from(orientation),
+                    );
+                    plane.material.color = color(GizmoHandle::Plane(a, b));
+                }
+
+                objects.extend(sticks.iter().map(|stick| stick as &dyn Object));
+                objects.extend(self.planes.iter().map(|plane| plane as &dyn Object));
+            }
+            GizmoMode::Rotate => {
+                for (i, ring) in self.rings.iter_mut().enumerate() {
+                    ring.set_transformation(placement(axes[i]));
+                    ring.material.color = color(GizmoHandle::Axis(i));
+                }
+
+                objects.extend(self.rings.iter().map(|ring| ring as &dyn Object));
+            }
+        }
+
+        objects
+    }
+
+    /// The handle under the ray, if any.
+    fn hit(&self, camera: &Camera, ray: &Ray, transform: &ModelTransform) -> Option<GizmoHandle> {
+        let center = transform.position;
+        let size = size(camera, center);
+        let axes = axes(self.active_mode(), transform);
+        let grab_distance = GRAB_DISTANCE * size;
+
+        match self.active_mode() {
+            GizmoMode::Translate | GizmoMode::Scale => {
+                // planes first, they sit between the axes
+                for &(a, b) in PLANES.iter() {
+                    let (u, v) = (axes[a].normalize(), axes[b].normalize());
+                    let plane_center = center + (u + v) * PLANE_OFFSET * size;
+
+                    if let Some(t) = ray.intersect_plane(plane_center, u.cross(v)) {
+                        let offset = ray.at(t) - plane_center;
+
+                        if offset.dot(u).abs() <= PLANE_SIZE * size
+                            && offset.dot(v).abs() <= PLANE_SIZE * size
+                        {
+                            return Some(GizmoHandle::Plane(a, b));
+                        }
+                    }
+                }
+
+                (0..3)
+                    .map(|i| {
+                        let end = center + axes[i].normalize() * size;
+                        (i, ray.distance_to_segment(center, end))
+                    })
+                    .filter(|&(_, distance)| distance <= grab_distance)
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(i, _)| GizmoHandle::Axis(i))
+            }
+            GizmoMode::Rotate => (0..3)
+                .filter_map(|i| {
+                    let t = ray.intersect_plane(center, axes[i].normalize())?;
+                    let off_ring = ((ray.at(t) - center).magnitude() - size).abs();
+                    (off_ring <= grab_distance).then_some((i, t))
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(i, _)| GizmoHandle::Axis(i)),
+        }
+    }
+
+    /// Where the ray meets the constraint of a handle, see [`Drag::grab`].
+    fn grab(
+        &self,
+        camera: &Camera,
+        ray: &Ray,
+        transform: &ModelTransform,
+        handle: GizmoHandle,
+    ) -> Option<Vec3> {
+        let center = transform.position;
+        let axes = axes(self.active_mode(), transform);
+
+        match (self.active_mode(), handle) {
+            (GizmoMode::Translate | GizmoMode::Scale, GizmoHandle::Axis(i)) => {
+                let axis = axes[i].normalize();
+                let (_, s) = ray.closest_to_line(center, axis)?;
+                Some(center + axis * s)
+            }
+            (GizmoMode::Translate | GizmoMode::Scale, GizmoHandle::Plane(a, b)) => {
+                let normal = axes[a].cross(axes[b]).normalize();
+                Some(ray.at(ray.intersect_plane(center, normal)?))
+            }
+            (GizmoMode::Rotate, GizmoHandle::Axis(i)) => {
+                let t = ray.intersect_plane(center, axes[i].normalize());
+                // looking along the ring, fall back to its edge
+                Some(t.map_or(center + camera.up() * size(camera, center), |t| ray.at(t)))
+            }
+            (GizmoMode::Rotate, GizmoHandle::Plane(..)) => None,
+        }
+    }
+
+    fn dragged(
+        &self,
+        camera: &Camera,
+        ray: &Ray,
+        drag: &Drag,
+        snap: bool,
+    ) -> Option<ModelTransform> {
+        let start = drag.start;
+        let center = start.position;
+        let current = self.grab(camera, ray, &start, drag.handle)?;
+        let axes = axes(self.active_mode(), &start);
+
+        let mut transform = start;
+
+        match (self.active_mode(), drag.handle) {
+            (GizmoMode::Translate, _) => {
+                let mut delta = current - drag.grab;
+
+                if snap {
+                    delta = vec3(
+                        snapped(delta.x, TRANSLATE_STEP),
+                        snapped(delta.y, TRANSLATE_STEP),
+                        snapped(delta.z, TRANSLATE_STEP),
+                    );
+                }
+
+                transform.position = start.position + delta;
+            }
+            (GizmoMode::Scale, handle) => {
+                let grabbed = (drag.grab - center).magnitude();
+
+                if grabbed < 1e-6 {
+                    return None;
+                }
+
+                // away from the center grows, past it mirrors
+                let sign = (current - center).dot(drag.grab - center).signum();
+                let factor = sign * (current - center).magnitude() / grabbed;
+
+                let components = match handle {
+                    GizmoHandle::Axis(i) => vec![i],
+                    GizmoHandle::Plane(a, b) => vec![a, b],
+                };
+
+                for i in components {
+                    let mut scale = start.scale[i] * factor;
+
+                    if snap {
+                        scale = snapped(scale, SCALE_STEP);
+                    }
+
+                    // a zero scale cannot be dragged back
+                    transform.scale[i] = if scale.abs() < 1e-3 { 1e-3 } else { scale };
+                }
+            }
+            (GizmoMode::Rotate, GizmoHandle::Axis(i)) => {
+                let axis = axes[i].normalize();
+                let from = drag.grab - center;
+                let to = current - center;
+
+                let angle: Deg<f32> = Rad(axis.dot(from.cross(to)).atan2(from.dot(to))).into();
+                let mut angle = angle.0;
+
+                if snap {
+                    angle = snapped(angle, ROTATE_STEP);
+                }
+
+                transform.rotation[i] = start.rotation[i] + angle;
+            }
+            (GizmoMode::Rotate, GizmoHandle::Plane(..)) => return None,
+        }
+
+        Some(transform)
+    }
+}
+
+fn overlay() -> ColorMaterial {
+    let mut material = ColorMaterial::default();
+    // drawn over everything so it can always be grabbed
+    material.render_states.depth_test = DepthTest::Always;
+    material
+}
+
+fn size(camera: &Camera, center: Vec3) -> f32 {
+    (camera.position() - center).magnitude() * SCREEN_SIZE
+}
+
+/// The directions the handles of a mode follow, see [`Gizmo`].
+fn axes(mode: GizmoMode, transform: &ModelTransform) -> [Vec3; 3] {
+    let rotate_x = Mat3::from_angle_x(Deg(transform.rotation.x));
+    let rotate_y = Mat3::from_angle_y(Deg(transform.rotation.y));
+    let rotate_z = Mat3::from_angle_z(Deg(transform.rotation.z));
+
+    let (x, y, z) = (
+        vec3(1.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        vec3(0.0, 0.0, 1.0),
+    );
+
+    match mode {
+        GizmoMode::Translate => [x, y, z],
+        GizmoMode::Rotate => [rotate_z * rotate_y * x, rotate_z * y, z],
+        GizmoMode::Scale => {
+            let rotation = rotate_z * rotate_y * rotate_x;
+            [rotation * x, rotation * y, rotation * z]
+        }
+    }
+}
+
+fn snapped(value: f32, step: f32) -> f32 {
+    (value / step).round() * step
+}
+
+/// Joins two indexed meshes without normals or other attributes.
+fn merge(a: &CpuMesh, b: &CpuMesh) -> CpuMesh {
+    let mut positions = a.positions.to_f32();
+    let offset = positions.len() as u32;
+    positions.extend(b.positions.to_f32());
+
+    let mut indices = Vec::new();
+    a.for_each_triangle(|i, j, k| indices.extend([i as u32, j as u32, k as u32]));
+    b.for_each_triangle(|i, j, k| {
+        indices.extend([i as u32 + offset, j as u32 + offset, k as u32 + offset])
+    });
+
+    CpuMesh {
+        positions: three_d::Positions::F32(positions),
+        indices: three_d::Indices::U32(indices),
+        ..Default::default()
+    }
+}
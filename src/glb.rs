@@ -0,0 +1,241 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use serde_json::{Value, json};
+use three_d::{CpuMaterial, CpuMesh, Mat4};
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_VERSION: u32 = 2;
+const CHUNK_JSON: u32 = 0x4E4F534A;
+const CHUNK_BIN: u32 = 0x004E4942;
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const UNSIGNED_BYTE: u32 = 5121;
+const UNSIGNED_INT: u32 = 5125;
+const FLOAT: u32 = 5126;
+const TRIANGLES: u32 = 4;
+
+/// A mesh with its material and placement in an exported scene.
+pub struct GlbNode<'a> {
+    pub name: &'a str,
+    pub mesh: &'a CpuMesh,
+    pub material: &'a CpuMaterial,
+    pub transformation: Mat4,
+}
+
+pub fn export_glb(path: &Path, nodes: &[GlbNode]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_glb(&mut writer, nodes)?;
+    writer.flush()
+}
+
+/// Writes the nodes as a binary glTF 2.0 scene with one mesh and one material
+/// per node. Material textures are not exported, only their factors.
+pub fn write_glb(writer: &mut impl Write, nodes: &[GlbNode]) -> io::Result<()> {
+    let mut builder = Builder::default();
+
+    let mut json_nodes = Vec::new();
+    let mut meshes = Vec::new();
+    let mut materials = Vec::new();
+
+    for (index, node) in nodes.iter().enumerate() {
+        let primitive = builder.primitive(node.mesh, index);
+
+        meshes.push(json!({ "name": node.name, "primitives": [primitive] }));
+        materials.push(material(node.material));
+
+        let matrix: &[f32; 16] = node.transformation.as_ref();
+        json_nodes.push(json!({ "name": node.name, "mesh": index, "matrix": matrix }));
+    }
+
+    let document = json!({
+        "asset": { "version": "2.0", "generator": "rene" },
+        "scene": 0,
+        "scenes": [{ "nodes": (0..nodes.len()).collect::<Vec<_>>() }],
+        "nodes": json_nodes,
+        "meshes": meshes,
+        "materials": materials,
+        "buffers": [{ "byteLength": builder.buffer.len() }],
+        "bufferViews": builder.buffer_views,
+        "accessors": builder.accessors,
+    });
+
+    let mut json_chunk = serde_json::to_vec(&document)?;
+    pad(&mut json_chunk, b' ');
+
+    let mut bin_chunk = builder.buffer;
+    pad(&mut bin_chunk, 0);
+
+    let total_length = 12 + 8 + json_chunk.len() + 8 + bin_chunk.len();
+
+    writer.write_all(GLB_MAGIC)?;
+    writer.write_all(&GLB_VERSION.to_le_bytes())?;
+    writer.write_all(&(total_length as u32).to_le_bytes())?;
+
+    writer.write_all(&(json_chunk.len() as u32).to_le_bytes())?;
+    writer.write_all(&CHUNK_JSON.to_le_bytes())?;
+    writer.write_all(&json_chunk)?;
+
+    writer.write_all(&(bin_chunk.len() as u32).to_le_bytes())?;
+    writer.write_all(&CHUNK_BIN.to_le_bytes())?;
+    writer.write_all(&bin_chunk)
+}
+
+fn material(material: &CpuMaterial) -> Value {
+    let base_color: [f32; 4] = material.albedo.into();
+
+    json!({
+        "name": material.name,
+        "pbrMetallicRoughness": {
+            "baseColorFactor": base_color,
+            "metallicFactor": material.metallic,
+            "roughnessFactor": material.roughness,
+        },
+        "alphaMode": if base_color[3] < 1.0 { "BLEND" } else { "OPAQUE" },
+    })
+}
+
+/// Pads a chunk to the 4 byte alignment required by GLB.
+fn pad(chunk: &mut Vec<u8>, value: u8) {
+    while !chunk.len().is_multiple_of(4) {
+        chunk.push(value);
+    }
+}
+
+/// Collects vertex data into a single binary buffer along with its views and accessors.
+#[derive(Default)]
+struct Builder {
+    buffer: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+}
+
+impl Builder {
+    fn primitive(&mut self, mesh: &CpuMesh, material: usize) -> Value {
+        let positions = mesh.positions.to_f32();
+
+        let (min, max) = positions
+            .iter()
+            .fold(([f32::MAX; 3], [f32::MIN; 3]), |(min, max), p| {
+                (
+                    [min[0].min(p.x), min[1].min(p.y), min[2].min(p.z)],
+                    [max[0].max(p.x), max[1].max(p.y), max[2].max(p.z)],
+                )
+            });
+
+        let position_bytes = positions
+            .iter()
+            .flat_map(|p| [p.x, p.y, p.z])
+            .flat_map(f32::to_le_bytes)
+            .collect::<Vec<_>>();
+
+        let position = self.accessor(
+            &position_bytes,
+            ARRAY_BUFFER,
+            json!({
+                "componentType": FLOAT,
+                "count": positions.len(),
+                "type": "VEC3",
+                "min": min,
+                "max": max,
+            }),
+        );
+
+        let mut attributes = json!({ "POSITION": position });
+
+        if let Some(normals) = &mesh.normals {
+            let bytes = normals
+                .iter()
+                .flat_map(|n| [n.x, n.y, n.z])
+                .flat_map(f32::to_le_bytes)
+                .collect::<Vec<_>>();
+
+            attributes["NORMAL"] = self
+                .accessor(
+                    &bytes,
+                    ARRAY_BUFFER,
+                    json!({ "componentType": FLOAT, "count": normals.len(), "type": "VEC3" }),
+                )
+                .into();
+        }
+
+        if let Some(uvs) = &mesh.uvs {
+            let bytes = uvs
+                .iter()
+                .flat_map(|uv| [uv.x, uv.y])
+                .flat_map(f32::to_le_bytes)
+                .collect::<Vec<_>>();
+
+            attributes["TEXCOORD_0"] = self
+                .accessor(
+                    &bytes,
+                    ARRAY_BUFFER,
+                    json!({ "componentType": FLOAT, "count": uvs.len(), "type": "VEC2" }),
+                )
+                .into();
+        }
+
+        if let Some(colors) = &mesh.colors {
+            let bytes = colors
+                .iter()
+                .flat_map(|c| [c.r, c.g, c.b, c.a])
+                .collect::<Vec<_>>();
+
+            attributes["COLOR_0"] = self
+                .accessor(
+                    &bytes,
+                    ARRAY_BUFFER,
+                    json!({
+                        "componentType": UNSIGNED_BYTE,
+                        "normalized": true,
+                        "count": colors.len(),
+                        "type": "VEC4",
+                    }),
+                )
+                .into();
+        }
+
+        let mut indices = Vec::with_capacity(mesh.triangle_count() * 3);
+        mesh.for_each_triangle(|a, b, c| indices.extend([a as u32, b as u32, c as u32]));
+
+        let index_bytes = indices
+            .iter()
+            .flat_map(|i| i.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        let indices = self.accessor(
+            &index_bytes,
+            ELEMENT_ARRAY_BUFFER,
+            json!({ "componentType": UNSIGNED_INT, "count": indices.len(), "type": "SCALAR" }),
+        );
+
+        json!({
+            "attributes": attributes,
+            "indices": indices,
+            "material": material,
+            "mode": TRIANGLES,
+        })
+    }
+
+    /// Appends `bytes` as a new buffer view and returns the index of an accessor into it.
+    fn accessor(&mut self, bytes: &[u8], target: u32, mut accessor: Value) -> usize {
+        pad(&mut self.buffer, 0);
+
+        accessor["bufferView"] = self.buffer_views.len().into();
+
+        self.buffer_views.push(json!({
+            "buffer": 0,
+            "byteOffset": self.buffer.len(),
+            "byteLength": bytes.len(),
+            "target": target,
+        }));
+        self.buffer.extend_from_slice(bytes);
+
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+}
@@ -0,0 +1,109 @@
+use std::{ffi::CString, fmt, ops::Deref, sync::Arc};
+
+use glutin::{
+    api::egl::{context::PossiblyCurrentContext, device::Device, display::Display},
+    config::{ConfigSurfaceTypes, ConfigTemplateBuilder},
+    context::{ContextApi, ContextAttributesBuilder, Version},
+    prelude::*,
+};
+use three_d::{Context, CoreError};
+
+/// Mesa's llvmpipe device, which renders on the cpu.
+const SOFTWARE_DEVICE_EXTENSION: &str = "EGL_MESA_device_software";
+
+#[derive(Debug)]
+pub enum HeadlessError {
+    Egl(glutin::error::Error),
+    NoDevice,
+    NoConfig,
+    Context(CoreError),
+}
+
+impl fmt::Display for HeadlessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeadlessError::Egl(error) => write!(f, "EGL: {error}"),
+            HeadlessError::NoDevice => write!(f, "no EGL device to render with"),
+            HeadlessError::NoConfig => write!(f, "no EGL config to render with"),
+            HeadlessError::Context(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for HeadlessError {}
+
+impl From<glutin::error::Error> for HeadlessError {
+    fn from(error: glutin::error::Error) -> Self {
+        HeadlessError::Egl(error)
+    }
+}
+
+impl From<CoreError> for HeadlessError {
+    fn from(error: CoreError) -> Self {
+        HeadlessError::Context(error)
+    }
+}
+
+/// A GL context on an EGL device, needing neither a window nor a display.
+///
+/// Mesa's software device is preferred, so images come out the same with or
+/// without a GPU. Only offscreen render targets can be drawn to.
+pub struct OffscreenContext {
+    context: Context,
+    _gl_context: PossiblyCurrentContext,
+}
+
+impl OffscreenContext {
+    pub fn new() -> Result<Self, HeadlessError> {
+        let mut devices: Vec<Device> = Device::query_devices()?.collect();
+        devices.sort_by_key(|device| !device.extensions().contains(SOFTWARE_DEVICE_EXTENSION));
+
+        let mut last_error = HeadlessError::NoDevice;
+
+        for device in devices.iter() {
+            match Self::with_device(device) {
+                Ok(context) => return Ok(context),
+                Err(error) => last_error = error,
+            }
+        }
+
+        Err(last_error)
+    }
+
+    fn with_device(device: &Device) -> Result<Self, HeadlessError> {
+        let display = unsafe { Display::with_device(device, None) }?;
+
+        // no surfaces, everything is drawn into textures
+        let template = ConfigTemplateBuilder::new()
+            .with_surface_type(ConfigSurfaceTypes::empty())
+            .build();
+        let config = unsafe { display.find_configs(template) }?
+            .next()
+            .ok_or(HeadlessError::NoConfig)?;
+
+        let attributes = ContextAttributesBuilder::new()
+            .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
+            .build(None);
+        let gl_context =
+            unsafe { display.create_context(&config, &attributes) }?.make_current_surfaceless()?;
+
+        let gl = unsafe {
+            three_d::context::Context::from_loader_function(|name| {
+                CString::new(name).map_or(std::ptr::null(), |name| display.get_proc_address(&name))
+            })
+        };
+
+        Ok(Self {
+            context: Context::from_gl_context(Arc::new(gl))?,
+            _gl_context: gl_context,
+        })
+    }
+}
+
+impl Deref for OffscreenContext {
+    type Target = Context;
+
+    fn deref(&self) -> &Self::Target {
+        &self.context
+    }
+}
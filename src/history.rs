@@ -0,0 +1,180 @@
+/// A reversible change to a `T`, holding whatever it needs to go both ways.
+pub trait Command<T> {
+    fn undo(&mut self, target: &mut T);
+    fn redo(&mut self, target: &mut T);
+}
+
+/// Undo and redo stacks of commands that have already been applied.
+pub struct History<C> {
+    done: Vec<C>,
+    undone: Vec<C>,
+    /// Oldest commands are dropped past this many.
+    limit: usize,
+}
+
+impl<C> History<C> {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            done: Vec::new(),
+            undone: Vec::new(),
+            limit,
+        }
+    }
+
+    /// Records a command the caller already applied, which ends anything left to redo.
+    pub fn push(&mut self, command: C) {
+        self.undone.clear();
+        self.done.push(command);
+
+        if self.done.len() > self.limit {
+            let excess = self.done.len() - self.limit;
+            self.done.drain(..excess);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Reverts the last command, returning whether there was one.
+    pub fn undo<T>(&mut self, target: &mut T) -> bool
+    where
+        C: Command<T>,
+    {
+        let Some(mut command) = self.done.pop() else {
+            return false;
+        };
+
+        command.undo(target);
+        self.undone.push(command);
+        true
+    }
+
+    /// Applies the last undone command again, returning whether there was one.
+    pub fn redo<T>(&mut self, target: &mut T) -> bool
+    where
+        C: Command<T>,
+    {
+        let Some(mut command) = self.undone.pop() else {
+            return false;
+        };
+
+        command.redo(target);
+        self.done.push(command);
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Appends its value to a list.
+    struct Append(u32);
+
+    impl Command<Vec<u32>> for Append {
+        fn undo(&mut self, target: &mut Vec<u32>) {
+            assert_eq!(target.pop(), Some(self.0));
+        }
+
+        fn redo(&mut self, target: &mut Vec<u32>) {
+            target.push(self.0);
+        }
+    }
+
+    /// Applies `Append(value)` and records it.
+    fn append(history: &mut History<Append>, target: &mut Vec<u32>, value: u32) {
+        let mut command = Append(value);
+        command.redo(target);
+        history.push(command);
+    }
+
+    #[test]
+    fn undo_and_redo_go_in_reverse_order() {
+        let mut history = History::new(10);
+        let mut list = Vec::new();
+
+        for value in 1..=3 {
+            append(&mut history, &mut list, value);
+        }
+
+        assert!(history.undo(&mut list));
+        assert!(history.undo(&mut list));
+        assert_eq!(list, [1]);
+        assert!(history.can_undo() && history.can_redo());
+
+        assert!(history.redo(&mut list));
+        assert_eq!(list, [1, 2]);
+        assert!(history.redo(&mut list));
+        assert_eq!(list, [1, 2, 3]);
+        assert!(!history.redo(&mut list));
+
+        for _ in 0..3 {
+            assert!(history.undo(&mut list));
+        }
+        assert!(list.is_empty());
+        assert!(!history.undo(&mut list));
+    }
+
+    #[test]
+    fn push_clears_redo() {
+        let mut history = History::new(10);
+        let mut list = Vec::new();
+
+        append(&mut history, &mut list, 1);
+        append(&mut history, &mut list, 2);
+        history.undo(&mut list);
+        assert!(history.can_redo());
+
+        append(&mut history, &mut list, 3);
+        assert!(!history.can_redo());
+        assert!(!history.redo(&mut list));
+        assert_eq!(list, [1, 3]);
+
+        history.undo(&mut list);
+        history.undo(&mut list);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn limit_drops_the_oldest_commands() {
+        let mut history = History::new(2);
+        let mut list = Vec::new();
+
+        for value in 1..=4 {
+            append(&mut history, &mut list, value);
+        }
+
+        assert!(history.undo(&mut list));
+        assert!(history.undo(&mut list));
+        assert!(!history.undo(&mut list));
+        assert_eq!(list, [1, 2]);
+
+        // the dropped commands are not redone either
+        while history.redo(&mut list) {}
+        assert_eq!(list, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn clear_forgets_both_directions() {
+        let mut history = History::new(10);
+        let mut list = Vec::new();
+
+        append(&mut history, &mut list, 1);
+        append(&mut history, &mut list, 2);
+        history.undo(&mut list);
+        history.clear();
+
+        assert!(!history.can_undo() && !history.can_redo());
+        assert_eq!(list, [1]);
+    }
+}
@@ -1,5 +1,25 @@
 // pub mod arrow;
 pub mod arrow;
+pub mod bvh;
 pub mod curves;
+pub mod export;
+pub mod fly_through;
+pub mod gizmo;
+pub mod glb;
+pub mod headless;
+pub mod history;
+pub mod lights;
+pub mod loader;
+pub mod model;
+pub mod navigation;
+pub mod obj_curves;
+pub mod pick;
+pub mod points;
+pub mod project;
+pub mod ray;
+pub mod settings;
+pub mod snapshot;
+pub mod spline;
 pub mod tube;
+pub mod view;
 pub mod wireframe;
@@ -0,0 +1,167 @@
+use three_d::{
+    AmbientLight, Attenuation, Context, Deg, DirectionalLight, Geometry, InnerSpace, Light,
+    PointLight, SpotLight, Srgba, Vec3, vec3,
+};
+
+/// Width and height of the shadow maps of directional and spot lights.
+pub const SHADOW_MAP_SIZE: u32 = 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LightType {
+    Ambient,
+    Directional,
+    Point,
+    Spot,
+}
+
+impl LightType {
+    pub const ALL: [LightType; 4] = [
+        LightType::Ambient,
+        LightType::Directional,
+        LightType::Point,
+        LightType::Spot,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LightType::Ambient => "Ambient",
+            LightType::Directional => "Directional",
+            LightType::Point => "Point",
+            LightType::Spot => "Spot",
+        }
+    }
+
+    pub fn has_position(&self) -> bool {
+        matches!(self, LightType::Point | LightType::Spot)
+    }
+
+    pub fn has_direction(&self) -> bool {
+        matches!(self, LightType::Directional | LightType::Spot)
+    }
+
+    /// Only directional and spot lights have shadow maps.
+    pub fn has_shadows(&self) -> bool {
+        self.has_direction()
+    }
+}
+
+/// Description of a light, turned into one to render with [`SceneLight::new`].
+///
+/// Fields a light type does not use are kept, so switching back restores them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LightSettings {
+    pub light_type: LightType,
+    pub color: Srgba,
+    pub intensity: f32,
+    pub position: Vec3,
+    pub direction: Vec3,
+    /// Half the opening angle of a spot light, in degrees.
+    pub cutoff: f32,
+    pub shadows: bool,
+}
+
+impl LightSettings {
+    pub fn new(light_type: LightType) -> Self {
+        Self {
+            light_type,
+            color: Srgba::WHITE,
+            intensity: match light_type {
+                LightType::Ambient => 0.4,
+                LightType::Directional => 2.0,
+                LightType::Point | LightType::Spot => 5.0,
+            },
+            position: vec3(0.0, 1.0, 1.0),
+            direction: vec3(0.0, -1.0, -1.0),
+            cutoff: 30.0,
+            shadows: false,
+        }
+    }
+
+    pub fn casts_shadows(&self) -> bool {
+        self.shadows && self.light_type.has_shadows()
+    }
+}
+
+/// The lights the viewer starts with.
+pub fn default_lights() -> Vec<LightSettings> {
+    vec![
+        LightSettings::new(LightType::Ambient),
+        LightSettings {
+            color: Srgba::new_opaque(204, 178, 127),
+            intensity: 10.0,
+            ..LightSettings::new(LightType::Directional)
+        },
+    ]
+}
+
+/// A light ready to render with.
+pub enum SceneLight {
+    Ambient(AmbientLight),
+    Directional(DirectionalLight),
+    Point(PointLight),
+    Spot(SpotLight),
+}
+
+impl SceneLight {
+    pub fn new(context: &Context, settings: &LightSettings) -> Self {
+        let LightSettings {
+            color,
+            intensity,
+            position,
+            direction,
+            ..
+        } = *settings;
+
+        // a zero direction would light nothing
+        let direction = if direction.magnitude2() > f32::EPSILON {
+            direction
+        } else {
+            vec3(0.0, -1.0, 0.0)
+        };
+
+        match settings.light_type {
+            LightType::Ambient => SceneLight::Ambient(AmbientLight::new(context, intensity, color)),
+            LightType::Directional => {
+                SceneLight::Directional(DirectionalLight::new(context, intensity, color, direction))
+            }
+            LightType::Point => SceneLight::Point(PointLight::new(
+                context,
+                intensity,
+                color,
+                position,
+                Attenuation::default(),
+            )),
+            LightType::Spot => SceneLight::Spot(SpotLight::new(
+                context,
+                intensity,
+                color,
+                position,
+                direction,
+                Deg(settings.cutoff),
+                Attenuation::default(),
+            )),
+        }
+    }
+
+    pub fn light(&self) -> &dyn Light {
+        match self {
+            SceneLight::Ambient(light) => light,
+            SceneLight::Directional(light) => light,
+            SceneLight::Point(light) => light,
+            SceneLight::Spot(light) => light,
+        }
+    }
+
+    /// Renders the shadows `geometries` cast, lights without shadow maps ignore this.
+    pub fn generate_shadow_map(&mut self, geometries: &[&dyn Geometry]) {
+        match self {
+            SceneLight::Directional(light) => {
+                light.generate_shadow_map(SHADOW_MAP_SIZE, geometries.iter().copied())
+            }
+            SceneLight::Spot(light) => {
+                light.generate_shadow_map(SHADOW_MAP_SIZE, geometries.iter().copied())
+            }
+            SceneLight::Ambient(_) | SceneLight::Point(_) => {}
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::{fmt, path::Path};
+
+use three_d_asset::Scene;
+
+use crate::{
+    model::{LoadError, read_scene},
+    obj_curves::{ObjCurve, ObjCurveError, load_obj_curves},
+    points::{PointList, PointsError, is_point_file, load_points},
+};
+
+#[derive(Debug)]
+pub enum FileError {
+    Points(PointsError),
+    Curves(ObjCurveError),
+    Model(LoadError),
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileError::Points(error) => write!(f, "unable to read points: {error}"),
+            FileError::Curves(error) => write!(f, "unable to read curves: {error}"),
+            FileError::Model(error) => write!(f, "unable to load model: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for FileError {}
+
+/// Everything read from a file picked in the viewer, before any gpu resources are created.
+#[derive(Default)]
+pub struct FileContents {
+    pub points: Option<PointList>,
+    pub scene: Option<Scene>,
+    pub curves: Vec<ObjCurve>,
+    /// Problems with parts of the file that did not stop the rest from loading.
+    pub errors: Vec<FileError>,
+}
+
+/// Reads point files as tube paths, and model files as meshes along with any
+/// curves an OBJ file carries. Does not touch the gpu, so it can run on a worker thread.
+pub fn read_file(path: &Path) -> FileContents {
+    let mut contents = FileContents::default();
+
+    if is_point_file(path) {
+        match load_points(path) {
+            Ok(points) => contents.points = Some(points),
+            Err(error) => contents.errors.push(FileError::Points(error)),
+        }
+
+        return contents;
+    }
+
+    let mut load_mesh = true;
+
+    if path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("obj"))
+    {
+        match load_obj_curves(path) {
+            Ok(obj_curves) => {
+                contents.curves = obj_curves.curves;
+                // files carrying only paths have no model to show
+                load_mesh = obj_curves.has_faces;
+            }
+            Err(error) => contents.errors.push(FileError::Curves(error)),
+        }
+    }
+
+    if load_mesh {
+        match read_scene(path) {
+            Ok(scene) => contents.scene = Some(scene),
+            Err(error) => contents.errors.push(FileError::Model(error)),
+        }
+    }
+
+    contents
+}
@@ -0,0 +1,397 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use three_d::{
+    AxisAlignedBoundingBox, Context, CpuGeometry, CpuMaterial, CpuMesh, Deg, Gm, Indices,
+    InnerSpace, Mat4, Mesh, PhysicalMaterial, Positions, SquareMatrix, Vec3, vec3,
+};
+use three_d_asset::{Node, Scene};
+
+use crate::bvh::Bvh;
+
+/// A renderable mesh of a loaded model, along with the cpu data it was created from.
+pub struct ModelPart {
+    pub name: String,
+    pub visible: bool,
+    pub cpu_mesh: CpuMesh,
+    pub cpu_material: CpuMaterial,
+    /// Transformation from the file, accumulated over all parent nodes.
+    pub transformation: Mat4,
+    pub gm: Gm<Mesh, PhysicalMaterial>,
+    /// Triangles of `cpu_mesh` for picking, in the space of the mesh.
+    pub bvh: Bvh,
+}
+
+/// A node in the hierarchy of a loaded model, referencing parts by index.
+#[derive(Clone, Debug)]
+pub struct ModelNode {
+    pub name: String,
+    pub visible: bool,
+    pub parts: Vec<usize>,
+    pub children: Vec<ModelNode>,
+}
+
+/// Every object of a model file, flattened into `parts` and organised by `root`,
+/// which carries the name and visibility of the whole model.
+pub struct LoadedModel {
+    pub parts: Vec<ModelPart>,
+    pub root: ModelNode,
+    /// File the model was read from.
+    path: PathBuf,
+    transform: ModelTransform,
+}
+
+/// Placement of a whole model in the scene, on top of the transformations from the file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ModelTransform {
+    pub position: Vec3,
+    /// Euler angles in degrees, applied around x, then y, then z.
+    pub rotation: Vec3,
+    pub scale: Vec3,
+}
+
+impl Default for ModelTransform {
+    fn default() -> Self {
+        Self {
+            position: vec3(0.0, 0.0, 0.0),
+            rotation: vec3(0.0, 0.0, 0.0),
+            scale: vec3(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl ModelTransform {
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_translation(self.position)
+            * Mat4::from_angle_z(Deg(self.rotation.z))
+            * Mat4::from_angle_y(Deg(self.rotation.y))
+            * Mat4::from_angle_x(Deg(self.rotation.x))
+            * Mat4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    InvalidPath(PathBuf),
+    Asset(three_d_asset::Error),
+    NoMeshes,
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::InvalidPath(path) => write!(f, "{:?} is not a file", path),
+            LoadError::Asset(error) => write!(f, "{error}"),
+            LoadError::NoMeshes => write!(f, "the file contains no triangle meshes"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Asset(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<three_d_asset::Error> for LoadError {
+    fn from(error: three_d_asset::Error) -> Self {
+        LoadError::Asset(error)
+    }
+}
+
+/// Reads and deserializes a model file without touching the gpu, so it can run on a worker thread.
+pub fn read_scene(path: &Path) -> Result<Scene, LoadError> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| LoadError::InvalidPath(path.to_path_buf()))?;
+
+    let mut raw_assets = three_d_asset::io::load(&[path])?;
+    let scene: Scene = raw_assets.deserialize(file_name)?;
+
+    if !scene.children.iter().any(has_triangles) {
+        return Err(LoadError::NoMeshes);
+    }
+
+    Ok(scene)
+}
+
+/// Loads and deserializes a model file, building gpu resources for all of its meshes.
+pub fn load_model(context: &Context, path: &Path) -> Result<LoadedModel, LoadError> {
+    let scene = read_scene(path)?;
+    Ok(LoadedModel::new(context, path, scene))
+}
+
+fn has_triangles(node: &Node) -> bool {
+    matches!(node.geometry, Some(CpuGeometry::Triangles(_)))
+        || node.children.iter().any(has_triangles)
+}
+
+impl ModelNode {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            visible: true,
+            parts: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Calls `callback` with the index of every part below this node that is
+    /// visible along with all of its ancestors.
+    pub fn for_each_visible_part(&self, parts: &[ModelPart], callback: &mut impl FnMut(usize)) {
+        if !self.visible {
+            return;
+        }
+
+        for &index in self.parts.iter() {
+            if parts[index].visible {
+                callback(index);
+            }
+        }
+
+        for child in self.children.iter() {
+            child.for_each_visible_part(parts, callback);
+        }
+    }
+}
+
+impl LoadedModel {
+    /// Builds gpu resources for every triangle mesh in the scene, keeping the node hierarchy.
+    pub fn new(context: &Context, path: &Path, scene: Scene) -> Self {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| scene.name.clone());
+
+        let materials: Vec<PhysicalMaterial> = scene
+            .materials
+            .iter()
+            .map(|material| PhysicalMaterial::new(context, material))
+            .collect();
+
+        let mut parts = Vec::new();
+        let mut root = ModelNode::new(name);
+
+        for node in scene.children {
+            root.children.push(visit(
+                context,
+                node,
+                Mat4::identity(),
+                &scene.materials,
+                &materials,
+                &mut parts,
+            ));
+        }
+
+        merge_siblings(&mut root);
+
+        Self {
+            parts,
+            root,
+            path: path.to_path_buf(),
+            transform: ModelTransform::default(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.root.name
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn transform(&self) -> ModelTransform {
+        self.transform
+    }
+
+    /// Moves the whole model, updating the transformation of every part.
+    pub fn set_transform(&mut self, transform: ModelTransform) {
+        self.transform = transform;
+
+        let matrix = transform.matrix();
+        for part in self.parts.iter_mut() {
+            part.gm.set_transformation(matrix * part.transformation);
+        }
+    }
+
+    /// Transformation of a part in the scene, the model transform included.
+    pub fn part_transformation(&self, index: usize) -> Mat4 {
+        self.transform.matrix() * self.parts[index].transformation
+    }
+
+    /// Calls `callback` for every part that should currently be rendered.
+    pub fn for_each_visible_part<'a>(&'a self, mut callback: impl FnMut(&'a ModelPart)) {
+        self.root
+            .for_each_visible_part(&self.parts, &mut |index| callback(&self.parts[index]));
+    }
+
+    /// A copy of a part's mesh placed in the scene, for exporting.
+    pub fn part_mesh(&self, index: usize) -> CpuMesh {
+        let mut mesh = self.parts[index].cpu_mesh.clone();
+
+        // a transformation that cannot be inverted leaves no usable normals
+        if mesh.transform(self.part_transformation(index)).is_err() {
+            mesh.normals = None;
+            mesh.tangents = None;
+        } else if let Some(normals) = mesh.normals.as_mut() {
+            for normal in normals.iter_mut() {
+                *normal = normal.normalize();
+            }
+        }
+
+        mesh
+    }
+
+    /// The visible parts placed in the scene as a single mesh, for exporting.
+    pub fn mesh(&self) -> CpuMesh {
+        let mut meshes = Vec::new();
+        self.root.for_each_visible_part(&self.parts, &mut |index| {
+            meshes.push(self.part_mesh(index));
+        });
+        merge_meshes(&meshes)
+    }
+
+    /// Bounds of a part in the scene.
+    pub fn part_bounds(&self, index: usize) -> AxisAlignedBoundingBox {
+        self.parts[index]
+            .bvh
+            .bounds()
+            .transformed(self.part_transformation(index))
+    }
+
+    /// Bounds of the visible parts in the scene, empty when none are.
+    pub fn bounds(&self) -> AxisAlignedBoundingBox {
+        let mut bounds = AxisAlignedBoundingBox::EMPTY;
+        self.root.for_each_visible_part(&self.parts, &mut |index| {
+            bounds.expand_with_aabb(self.part_bounds(index));
+        });
+        bounds
+    }
+}
+
+fn visit(
+    context: &Context,
+    node: Node,
+    parent_transformation: Mat4,
+    cpu_materials: &[CpuMaterial],
+    materials: &[PhysicalMaterial],
+    parts: &mut Vec<ModelPart>,
+) -> ModelNode {
+    let transformation = parent_transformation * node.transformation;
+    let mut model_node = ModelNode::new(node.name);
+
+    if let Some(CpuGeometry::Triangles(mut cpu_mesh)) = node.geometry {
+        // files without normals would render unlit
+        if cpu_mesh.normals.is_none() {
+            cpu_mesh.compute_normals();
+        }
+
+        let (material, cpu_material) = match node.material_index {
+            Some(index) if index < materials.len() => {
+                (materials[index].clone(), cpu_materials[index].clone())
+            }
+            _ => (PhysicalMaterial::default(), CpuMaterial::default()),
+        };
+
+        // objects with several materials are split into one part per material
+        let name = if cpu_material.name.is_empty() {
+            model_node.name.clone()
+        } else {
+            cpu_material.name.clone()
+        };
+
+        let mut gm = Gm::new(Mesh::new(context, &cpu_mesh), material);
+        gm.set_transformation(transformation);
+        let bvh = Bvh::new(&cpu_mesh);
+
+        parts.push(ModelPart {
+            name,
+            visible: true,
+            cpu_mesh,
+            cpu_material,
+            transformation,
+            gm,
+            bvh,
+        });
+        model_node.parts.push(parts.len() - 1);
+    }
+
+    for child in node.children {
+        model_node.children.push(visit(
+            context,
+            child,
+            transformation,
+            cpu_materials,
+            materials,
+            parts,
+        ));
+    }
+
+    model_node
+}
+
+/// The OBJ loader emits one node per object and material, all named after
+/// the object. Join consecutive leaves with the same name back into one object.
+fn merge_siblings(node: &mut ModelNode) {
+    let mut merged: Vec<ModelNode> = Vec::new();
+
+    for child in node.children.drain(..) {
+        match merged.last_mut() {
+            Some(last)
+                if last.name == child.name
+                    && last.children.is_empty()
+                    && child.children.is_empty() =>
+            {
+                last.parts.extend(child.parts);
+            }
+            _ => merged.push(child),
+        }
+    }
+
+    for child in merged.iter_mut() {
+        merge_siblings(child);
+    }
+
+    node.children = merged;
+}
+
+/// Appends meshes into one, keeping normals and texture coordinates only when every mesh has them.
+fn merge_meshes(meshes: &[CpuMesh]) -> CpuMesh {
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+    let mut normals = meshes
+        .iter()
+        .all(|mesh| mesh.normals.is_some())
+        .then(Vec::new);
+    let mut uvs = meshes.iter().all(|mesh| mesh.uvs.is_some()).then(Vec::new);
+
+    for mesh in meshes {
+        let offset = positions.len() as u32;
+        mesh.for_each_triangle(|a, b, c| {
+            indices.extend([a, b, c].map(|index| offset + index as u32));
+        });
+        positions.extend(mesh.positions.to_f32());
+
+        if let (Some(normals), Some(mesh_normals)) = (normals.as_mut(), &mesh.normals) {
+            normals.extend_from_slice(mesh_normals);
+        }
+
+        if let (Some(uvs), Some(mesh_uvs)) = (uvs.as_mut(), &mesh.uvs) {
+            uvs.extend_from_slice(mesh_uvs);
+        }
+    }
+
+    CpuMesh {
+        positions: Positions::F32(positions),
+        indices: Indices::U32(indices),
+        normals,
+        uvs,
+        ..Default::default()
+    }
+}
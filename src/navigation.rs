@@ -0,0 +1,366 @@
+use std::collections::HashSet;
+
+use three_d::{Camera, Event, InnerSpace, Key, Mat3, MouseButton, PhysicalPoint, Rad, Vec3, vec3};
+use three_d_asset::ProjectionType;
+
+use crate::ray::Ray;
+
+/// Radians per logical pixel dragged, at a rotate speed of one.
+const ROTATE_STEP: f32 = 0.01;
+/// Fraction of the distance to the target per wheel step, at a zoom speed of one.
+const ZOOM_STEP: f32 = 0.01;
+/// Furthest the view may tilt up or down in Fly mode, in radians, short of
+/// straight up where the up direction stops defining the view.
+const MAX_PITCH: f32 = 1.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NavigationMode {
+    /// Left drag orbits around the target keeping the up direction.
+    Orbit,
+    /// Middle drag orbits, with Shift pans and with Ctrl zooms.
+    Blender,
+    /// Left drag rotates freely around the target, right drag pans.
+    Trackball,
+    /// WASD moves, Q and E go down and up, left drag looks around and right drag pans.
+    Fly,
+}
+
+impl NavigationMode {
+    pub const ALL: [NavigationMode; 4] = [
+        NavigationMode::Orbit,
+        NavigationMode::Blender,
+        NavigationMode::Trackball,
+        NavigationMode::Fly,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NavigationMode::Orbit => "Orbit",
+            NavigationMode::Blender => "Blender",
+            NavigationMode::Trackball => "Trackball",
+            NavigationMode::Fly => "Fly",
+        }
+    }
+
+    pub fn help(&self) -> &'static str {
+        match self {
+            NavigationMode::Orbit => "Left drag to orbit, scroll to zoom",
+            NavigationMode::Blender => {
+                "Middle drag to orbit, Shift+middle drag to pan, Ctrl+middle drag or scroll to zoom"
+            }
+            NavigationMode::Trackball => "Left drag to rotate, right drag to pan, scroll to zoom",
+            NavigationMode::Fly => {
+                "WASD to move, Q and E to go down and up, left drag to look, right drag to pan"
+            }
+        }
+    }
+}
+
+/// Camera controls for every [`NavigationMode`], keeping `target` and the
+/// camera target the same.
+pub struct Navigation {
+    pub mode: NavigationMode,
+    /// The point orbited around and zoomed towards.
+    pub target: Vec3,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// Multiplies how far a drag rotates.
+    pub rotate_speed: f32,
+    /// Multiplies how far a wheel step zooms.
+    pub zoom_speed: f32,
+    /// Units per second moved in fly mode.
+    pub fly_speed: f32,
+    /// Zooms towards the point under the cursor rather than the target.
+    pub zoom_to_cursor: bool,
+    /// Movement keys held down in fly mode.
+    held: HashSet<Key>,
+}
+
+impl Navigation {
+    pub fn new(target: Vec3, min_distance: f32, max_distance: f32) -> Self {
+        Self {
+            mode: NavigationMode::Orbit,
+            target,
+            min_distance,
+            max_distance,
+            rotate_speed: 1.0,
+            zoom_speed: 1.0,
+            fly_speed: 1.0,
+            zoom_to_cursor: false,
+            held: HashSet::new(),
+        }
+    }
+
+    /// Handles the events not handled yet and moves in fly mode for
+    /// `elapsed` seconds, returning whether the camera changed.
+    pub fn handle_events(
+        &mut self,
+        camera: &mut Camera,
+        events: &mut [Event],
+        elapsed: f32,
+    ) -> bool {
+        let mut change = false;
+
+        for event in events.iter_mut() {
+            match event {
+                Event::MouseMotion {
+                    delta,
+                    button: Some(button),
+                    modifiers,
+                    handled: handled @ false,
+                    ..
+                } => {
+                    let (dx, dy) = *delta;
+                    let rotate = ROTATE_STEP * self.rotate_speed;
+
+                    match (self.mode, *button) {
+                        (NavigationMode::Orbit, MouseButton::Left) => {
+                            camera.rotate_around_with_fixed_up(
+                                self.target,
+                                rotate * dx,
+                                rotate * dy,
+                            );
+                        }
+                        (NavigationMode::Blender, MouseButton::Middle) if modifiers.shift => {
+                            self.pan(camera, dx, dy);
+                        }
+                        (NavigationMode::Blender, MouseButton::Middle) if modifiers.ctrl => {
+                            self.zoom(camera, self.target, -dy * 0.5);
+                        }
+                        (NavigationMode::Blender, MouseButton::Middle) => {
+                            camera.rotate_around_with_fixed_up(
+                                self.target,
+                                rotate * dx,
+                                rotate * dy,
+                            );
+                        }
+                        (NavigationMode::Trackball, MouseButton::Left) => {
+                            self.trackball(camera, rotate * dx, rotate * dy);
+                        }
+                        (NavigationMode::Fly, MouseButton::Left) => {
+                            self.look(camera, rotate * dx * 0.2, rotate * dy * 0.2);
+                        }
+                        (NavigationMode::Trackball | NavigationMode::Fly, MouseButton::Right) => {
+                            self.pan(camera, dx, dy);
+                        }
+                        _ => continue,
+                    }
+
+                    *handled = true;
+                    change = true;
+                }
+                Event::MouseWheel {
+                    delta,
+                    position,
+                    handled: handled @ false,
+                    ..
+                } => {
+                    if self.mode == NavigationMode::Fly {
+                        let change = camera.view_direction() * self.fly_speed * delta.1 * 0.01;
+                        camera.translate(change);
+                        self.target = camera.target();
+                    } else {
+                        let point = self.zoom_point(camera, *position);
+                        self.zoom(camera, point, delta.1);
+                    }
+
+                    *handled = true;
+                    change = true;
+                }
+                Event::PinchGesture {
+                    delta,
+                    position,
+                    handled: handled @ false,
+                    ..
+                } => {
+                    let point = self.zoom_point(camera, *position);
+                    // a pinch is a fraction of the distance, scale it like wheel steps
+                    self.zoom(camera, point, *delta / (ZOOM_STEP * self.zoom_speed));
+
+                    *handled = true;
+                    change = true;
+                }
+                Event::KeyPress {
+                    kind,
+                    modifiers,
+                    handled: handled @ false,
+                } if self.mode == NavigationMode::Fly
+                    && fly_direction(*kind).is_some()
+                    && !modifiers.ctrl
+                    && !modifiers.command =>
+                {
+                    self.held.insert(*kind);
+                    *handled = true;
+                }
+                // released keys stop moving even when something else handled them
+                Event::KeyRelease { kind, .. } => {
+                    self.held.remove(kind);
+                }
+                Event::MouseLeave => self.held.clear(),
+                _ => {}
+            }
+        }
+
+        if self.mode != NavigationMode::Fly {
+            self.held.clear();
+        } else if !self.held.is_empty() {
+            change |= self.fly(camera, elapsed);
+        }
+
+        change
+    }
+
+    fn fly(&mut self, camera: &mut Camera, elapsed: f32) -> bool {
+        let forward = camera.view_direction();
+        let right = camera.right_direction();
+        let up = camera.up_orthogonal();
+
+        let direction = self
+            .held
+            .iter()
+            .filter_map(|&key| fly_direction(key))
+            .fold(vec3(0.0, 0.0, 0.0), |sum, local| {
+                sum + right * local.x + up * local.y + forward * local.z
+            });
+
+        if direction.magnitude2() < 1e-6 {
+            return false;
+        }
+
+        camera.translate(direction.normalize() * self.fly_speed * elapsed);
+        self.target = camera.target();
+        true
+    }
+
+    /// Turns the camera in place, keeping it from tilting past straight up or down.
+    fn look(&mut self, camera: &mut Camera, yaw: f32, pitch: f32) {
+        camera.yaw(Rad(yaw));
+
+        let up = camera.up().normalize();
+        let elevation = camera.view_direction().dot(up).clamp(-1.0, 1.0).asin();
+        let pitched = (elevation + pitch).clamp(-MAX_PITCH, MAX_PITCH);
+
+        camera.pitch(Rad(pitched - elevation));
+        self.target = camera.target();
+    }
+
+    /// Moves the camera and target in the view plane, so the point at the
+    /// target's depth follows the cursor.
+    fn pan(&mut self, camera: &mut Camera, dx: f32, dy: f32) {
+        let distance = (self.target - camera.position()).magnitude();
+        let view_height = match *camera.projection_type() {
+            ProjectionType::Perspective { field_of_view_y } => {
+                2.0 * distance * (field_of_view_y.0 * 0.5).tan()
+            }
+            ProjectionType::Orthographic { height } => height * distance,
+        };
+        let step = view_height / camera.viewport().height.max(1) as f32;
+
+        let change = -camera.right_direction() * dx * step + camera.up_orthogonal() * dy * step;
+        camera.translate(change);
+        self.target += change;
+    }
+
+    /// Rotates around the target about the axis across the drag in the view plane.
+    fn trackball(&mut self, camera: &mut Camera, x: f32, y: f32) {
+        let axis = camera.up_orthogonal() * x + camera.right_direction() * y;
+        let angle = axis.magnitude();
+
+        if angle < 1e-6 {
+            return;
+        }
+
+        // the camera moves the opposite way to the scene it drags
+        let rotation = Mat3::from_axis_angle(axis / angle, Rad(-angle));
+        let position = self.target + rotation * (camera.position() - self.target);
+        let up = rotation * camera.up_orthogonal();
+
+        camera.set_view(position, self.target, up);
+    }
+
+    /// The point on the plane through the target facing the camera under the cursor.
+    fn zoom_point(&self, camera: &Camera, position: PhysicalPoint) -> Vec3 {
+        if !self.zoom_to_cursor {
+            return self.target;
+        }
+
+        let ray = Ray::from_pixel(camera, position);
+        ray.intersect_plane(self.target, camera.view_direction())
+            .map_or(self.target, |t| ray.at(t))
+    }
+
+    fn zoom(&mut self, camera: &mut Camera, point: Vec3, steps: f32) {
+        let distance = (self.target - camera.position()).magnitude();
+        let amount = ZOOM_STEP * self.zoom_speed * distance * steps + 0.001 * steps.signum();
+
+        camera.zoom_towards(point, amount, self.min_distance, self.max_distance);
+        // zooming off the target moves it along
+        self.target = camera.target();
+    }
+}
+
+/// Movement of a fly mode key as right, up and forward.
+fn fly_direction(key: Key) -> Option<Vec3> {
+    match key {
+        Key::W | Key::ArrowUp => Some(vec3(0.0, 0.0, 1.0)),
+        Key::S | Key::ArrowDown => Some(vec3(0.0, 0.0, -1.0)),
+        Key::D | Key::ArrowRight => Some(vec3(1.0, 0.0, 0.0)),
+        Key::A | Key::ArrowLeft => Some(vec3(-1.0, 0.0, 0.0)),
+        Key::E => Some(vec3(0.0, 1.0, 0.0)),
+        Key::Q => Some(vec3(0.0, -1.0, 0.0)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use three_d::{Viewport, degrees};
+
+    fn camera() -> Camera {
+        Camera::new_perspective(
+            Viewport::new_at_origo(100, 100),
+            vec3(0.0, 0.0, 5.0),
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            degrees(45.0),
+            0.1,
+            100.0,
+        )
+    }
+
+    fn elevation(camera: &Camera) -> f32 {
+        camera.view_direction().dot(vec3(0.0, 1.0, 0.0)).asin()
+    }
+
+    #[test]
+    fn fly_look_stops_short_of_straight_up_and_down() {
+        let mut navigation = Navigation::new(Vec3::new(0.0, 0.0, 0.0), 0.1, 100.0);
+        let mut camera = camera();
+
+        // steps large enough to jump past the pole in one go
+        for _ in 0..10 {
+            navigation.look(&mut camera, 0.1, 0.7);
+            assert!(elevation(&camera) <= MAX_PITCH + 1e-4);
+        }
+        assert!((elevation(&camera) - MAX_PITCH).abs() < 1e-3);
+
+        for _ in 0..10 {
+            navigation.look(&mut camera, -0.1, -0.7);
+            assert!(elevation(&camera) >= -MAX_PITCH - 1e-4);
+        }
+        assert!((elevation(&camera) + MAX_PITCH).abs() < 1e-3);
+
+        assert_eq!(camera.up(), vec3(0.0, 1.0, 0.0));
+        assert!((navigation.target - camera.target()).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn fly_look_pitches_within_the_limit() {
+        let mut navigation = Navigation::new(Vec3::new(0.0, 0.0, 0.0), 0.1, 100.0);
+        let mut camera = camera();
+
+        navigation.look(&mut camera, 0.0, 0.3);
+        assert!((elevation(&camera) - 0.3).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,472 @@
+use std::{fmt, fs, path::Path};
+
+use three_d::Vec3;
+
+use crate::curves::{BSplineCurve, Curve, Polyline};
+
+/// A named path read from an OBJ file.
+pub struct ObjCurve {
+    pub name: String,
+    pub curve: Box<dyn Curve + Send>,
+}
+
+/// Curves read from an OBJ file, which may also carry a regular mesh.
+#[derive(Default)]
+pub struct ObjCurves {
+    pub curves: Vec<ObjCurve>,
+    pub has_faces: bool,
+}
+
+#[derive(Debug)]
+pub enum ObjCurveError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for ObjCurveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjCurveError::Io(error) => write!(f, "{error}"),
+            ObjCurveError::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ObjCurveError {}
+
+impl From<std::io::Error> for ObjCurveError {
+    fn from(error: std::io::Error) -> Self {
+        ObjCurveError::Io(error)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CurveType {
+    BSpline,
+    Bezier,
+}
+
+/// Free-form curve state collected between `curv` and `end`.
+struct FreeForm {
+    line: usize,
+    range: (f32, f32),
+    vertices: Vec<usize>,
+    parameters: Vec<f32>,
+}
+
+/// Polyline state, consecutive `l` elements sharing an end point are joined.
+/// Always holds at least two vertices.
+struct Lines {
+    vertices: Vec<usize>,
+}
+
+pub fn load_obj_curves(path: &Path) -> Result<ObjCurves, ObjCurveError> {
+    let source = fs::read_to_string(path)?;
+    parse_obj_curves(&source)
+}
+
+/// Extracts `l` polyline elements and `curv` free-form curves (`bspline` and
+/// `bezier`, optionally rational) from OBJ source text. All other statements are ignored.
+pub fn parse_obj_curves(source: &str) -> Result<ObjCurves, ObjCurveError> {
+    let mut result = ObjCurves::default();
+
+    let mut vertices: Vec<(Vec3, f32)> = Vec::new();
+    let mut name = String::new();
+    let mut curve_type = CurveType::BSpline;
+    let mut rational = false;
+    let mut degree = 1;
+    let mut lines: Option<Lines> = None;
+    let mut free_form: Option<FreeForm> = None;
+
+    let parse_error = |line: usize, message: String| ObjCurveError::Parse { line, message };
+
+    for (line_number, statement) in statements(source) {
+        let mut tokens = statement.split_whitespace();
+
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+
+        let arguments: Vec<&str> = tokens.collect();
+
+        let floats = |arguments: &[&str]| -> Result<Vec<f32>, ObjCurveError> {
+            arguments
+                .iter()
+                .map(|value| {
+                    value
+                        .parse::<f32>()
+                        .map_err(|error| parse_error(line_number, format!("{value}: {error}")))
+                })
+                .collect()
+        };
+
+        let vertex_index = |value: &str, count: usize| -> Result<usize, ObjCurveError> {
+            let index = value
+                .split('/')
+                .next()
+                .unwrap_or_default()
+                .parse::<i64>()
+                .map_err(|error| parse_error(line_number, format!("{value}: {error}")))?;
+
+            // 1-based, negative indices are relative to the last vertex
+            let resolved = if index < 0 {
+                count as i64 + index
+            } else {
+                index - 1
+            };
+
+            if resolved < 0 || resolved >= count as i64 {
+                return Err(parse_error(
+                    line_number,
+                    format!("vertex index {index} out of range"),
+                ));
+            }
+
+            Ok(resolved as usize)
+        };
+
+        if keyword != "l"
+            && let Some(finished) = lines.take()
+        {
+            result.curves.push(polyline(&name, &vertices, finished));
+        }
+
+        match keyword {
+            "v" => {
+                let values = floats(&arguments)?;
+
+                if values.len() < 3 {
+                    return Err(parse_error(line_number, "expected x y z".to_string()));
+                }
+
+                // a fourth coordinate is the weight of rational curves
+                let weight = if values.len() == 4 { values[3] } else { 1.0 };
+                vertices.push((Vec3::new(values[0], values[1], values[2]), weight));
+            }
+            "f" => result.has_faces = true,
+            "o" | "g" => name = arguments.join(" "),
+            "l" => {
+                if arguments.len() < 2 {
+                    return Err(parse_error(
+                        line_number,
+                        "expected at least two vertex indices".to_string(),
+                    ));
+                }
+
+                let indices = arguments
+                    .iter()
+                    .map(|value| vertex_index(value, vertices.len()))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                match lines.as_mut() {
+                    Some(current) if current.vertices.last() == indices.first() => {
+                        current.vertices.extend_from_slice(&indices[1..]);
+                    }
+                    _ => {
+                        if let Some(finished) = lines.take() {
+                            result.curves.push(polyline(&name, &vertices, finished));
+                        }
+                        lines = Some(Lines { vertices: indices });
+                    }
+                }
+            }
+            "cstype" => {
+                rational = arguments.first() == Some(&"rat");
+                curve_type = match arguments.last() {
+                    Some(&"bspline") => CurveType::BSpline,
+                    Some(&"bezier") => CurveType::Bezier,
+                    other => {
+                        return Err(parse_error(
+                            line_number,
+                            format!("unsupported curve type {:?}", other.unwrap_or(&"")),
+                        ));
+                    }
+                };
+            }
+            "deg" => {
+                degree = arguments
+                    .first()
+                    .and_then(|value| value.parse::<usize>().ok())
+                    .ok_or_else(|| parse_error(line_number, "expected degree".to_string()))?;
+            }
+            "curv" => {
+                if arguments.len() < 4 {
+                    return Err(parse_error(
+                        line_number,
+                        "expected u0 u1 and at least two control points".to_string(),
+                    ));
+                }
+
+                let range = floats(&arguments[..2])?;
+                let indices = arguments[2..]
+                    .iter()
+                    .map(|value| vertex_index(value, vertices.len()))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                free_form = Some(FreeForm {
+                    line: line_number,
+                    range: (range[0], range[1]),
+                    vertices: indices,
+                    parameters: Vec::new(),
+                });
+            }
+            "parm" => {
+                if let Some(current) = free_form.as_mut()
+                    && arguments.first() == Some(&"u")
+                {
+                    current.parameters = floats(&arguments[1..])?;
+                }
+            }
+            "end" => {
+                if let Some(finished) = free_form.take() {
+                    let control_points = finished.vertices.iter().map(|&i| vertices[i].0).collect();
+                    let weights = finished
+                        .vertices
+                        .iter()
+                        .map(|&i| if rational { vertices[i].1 } else { 1.0 })
+                        .collect();
+
+                    let knots = match curve_type {
+                        CurveType::BSpline => finished.parameters,
+                        // a single segment needs no parm statement
+                        CurveType::Bezier if finished.parameters.is_empty() => {
+                            bezier_knots(degree, &[finished.range.0, finished.range.1])
+                        }
+                        CurveType::Bezier => bezier_knots(degree, &finished.parameters),
+                    };
+
+                    let mut curve =
+                        BSplineCurve::new_rational(degree, control_points, weights, knots)
+                            .ok_or_else(|| {
+                                parse_error(
+                                    finished.line,
+                                    "knot vector does not match degree and control points"
+                                        .to_string(),
+                                )
+                            })?;
+                    curve.range = finished.range;
+
+                    result.curves.push(ObjCurve {
+                        name: name.clone(),
+                        curve: Box::new(curve),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(finished) = lines.take() {
+        result.curves.push(polyline(&name, &vertices, finished));
+    }
+
+    Ok(result)
+}
+
+/// Joins lines ending in `\` with the following line and strips comments,
+/// yielding each statement with its starting line number.
+fn statements(source: &str) -> impl Iterator<Item = (usize, String)> + '_ {
+    let mut lines = source.lines().enumerate();
+
+    std::iter::from_fn(move || {
+        let (index, first) = lines.next()?;
+        let mut statement = first.split('#').next().unwrap_or_default().to_string();
+
+        while statement.trim_end().ends_with('\\') {
+            statement = statement.trim_end().trim_end_matches('\\').to_string();
+            match lines.next() {
+                Some((_, next)) => {
+                    statement.push(' ');
+                    statement.push_str(next.split('#').next().unwrap_or_default());
+                }
+                None => break,
+            }
+        }
+
+        Some((index + 1, statement))
+    })
+}
+
+fn polyline(name: &str, vertices: &[(Vec3, f32)], lines: Lines) -> ObjCurve {
+    ObjCurve {
+        name: name.to_string(),
+        curve: Box::new(Polyline::new(
+            lines.vertices.iter().map(|&i| vertices[i].0).collect(),
+        )),
+    }
+}
+
+/// Expands Bézier segment boundaries into the equivalent B-spline knot vector.
+fn bezier_knots(degree: usize, boundaries: &[f32]) -> Vec<f32> {
+    let mut knots = Vec::new();
+
+    for (i, boundary) in boundaries.iter().enumerate() {
+        let multiplicity = if i == 0 || i == boundaries.len() - 1 {
+            degree + 1
+        } else {
+            degree
+        };
+        knots.extend(std::iter::repeat_n(*boundary, multiplicity));
+    }
+
+    knots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use three_d::InnerSpace;
+
+    const SQUARE: &str = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n";
+
+    #[test]
+    fn empty_source_has_no_curves() {
+        let result = parse_obj_curves("").unwrap();
+
+        assert!(result.curves.is_empty());
+        assert!(!result.has_faces);
+    }
+
+    #[test]
+    fn line_without_indices_is_an_error() {
+        let source = format!("{SQUARE}l\nl\n");
+
+        assert!(matches!(
+            parse_obj_curves(&source),
+            Err(ObjCurveError::Parse { line: 5, .. })
+        ));
+    }
+
+    #[test]
+    fn line_with_single_index_is_an_error() {
+        let source = format!("{SQUARE}l 1 2\nl 2\n");
+
+        assert!(matches!(
+            parse_obj_curves(&source),
+            Err(ObjCurveError::Parse { line: 6, .. })
+        ));
+    }
+
+    #[test]
+    fn lines_sharing_an_end_point_are_joined() {
+        let source = format!("{SQUARE}o path\nl 1 2\nl 2 3\nl 3 4\n");
+        let result = parse_obj_curves(&source).unwrap();
+
+        assert_eq!(result.curves.len(), 1);
+
+        let curve = &result.curves[0];
+        assert_eq!(curve.name, "path");
+        assert_eq!(curve.curve.get_point(0.0), Some(Vec3::new(0.0, 0.0, 0.0)));
+        assert_eq!(curve.curve.get_point(0.5), Some(Vec3::new(1.0, 0.5, 0.0)));
+        assert_eq!(curve.curve.get_point(1.0), Some(Vec3::new(0.0, 1.0, 0.0)));
+    }
+
+    #[test]
+    fn disconnected_lines_are_separate_curves() {
+        let source = format!("{SQUARE}l 1 2\nl 3 4\n");
+        let result = parse_obj_curves(&source).unwrap();
+
+        assert_eq!(result.curves.len(), 2);
+    }
+
+    fn assert_close(actual: Option<Vec3>, expected: Vec3) {
+        let actual = actual.expect("a point");
+        assert!(
+            (actual - expected).magnitude() < 1e-5,
+            "{actual:?} is not {expected:?}"
+        );
+    }
+
+    #[test]
+    fn b_spline_with_knots() {
+        let source = format!(
+            "{SQUARE}o arc\ncstype bspline\ndeg 2\ncurv 0 1 1 2 3 4\nparm u 0 0 0 0.5 1 1 1\nend\n"
+        );
+        let result = parse_obj_curves(&source).unwrap();
+
+        assert_eq!(result.curves.len(), 1);
+
+        let curve = &result.curves[0];
+        assert_eq!(curve.name, "arc");
+        assert_close(curve.curve.get_point(0.0), Vec3::new(0.0, 0.0, 0.0));
+        // halfway between the middle control points at the inner knot
+        assert_close(curve.curve.get_point(0.5), Vec3::new(1.0, 0.5, 0.0));
+        assert_close(curve.curve.get_point(1.0), Vec3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn bezier_without_parm_is_one_segment() {
+        let source = format!("{SQUARE}cstype bezier\ndeg 3\ncurv 0 1 1 2 3 4\nend\n");
+        let result = parse_obj_curves(&source).unwrap();
+
+        let curve = &result.curves[0].curve;
+        assert_close(curve.get_point(0.0), Vec3::new(0.0, 0.0, 0.0));
+        // (p0 + 3 p1 + 3 p2 + p3) / 8
+        assert_close(curve.get_point(0.5), Vec3::new(0.75, 0.5, 0.0));
+        assert_close(curve.get_point(1.0), Vec3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn bezier_segments_from_parm() {
+        let source = format!("{SQUARE}cstype bezier\ndeg 1\ncurv 0 2 1 2 3\nparm u 0 1 2\nend\n");
+        let result = parse_obj_curves(&source).unwrap();
+
+        let curve = &result.curves[0].curve;
+        assert_close(curve.get_point(0.25), Vec3::new(0.5, 0.0, 0.0));
+        assert_close(curve.get_point(0.5), Vec3::new(1.0, 0.0, 0.0));
+        assert_close(curve.get_point(0.75), Vec3::new(1.0, 0.5, 0.0));
+    }
+
+    #[test]
+    fn rational_curves_use_vertex_weights() {
+        // a quarter of the unit circle
+        let vertices = "v 1 0 0\nv 1 1 0 0.70710677\nv 0 1 0\n";
+
+        for cstype in [
+            "cstype rat bezier\ndeg 2\ncurv 0 1 1 2 3\nend\n",
+            "cstype rat bspline\ndeg 2\ncurv 0 1 1 2 3\nparm u 0 0 0 1 1 1\nend\n",
+        ] {
+            let source = format!("{vertices}{cstype}");
+            let curve = &parse_obj_curves(&source).unwrap().curves[0].curve;
+
+            for i in 0..=8 {
+                let point = curve.get_point(i as f32 / 8.0).unwrap();
+                assert!(
+                    (point.magnitude() - 1.0).abs() < 1e-5,
+                    "{cstype}: {point:?}"
+                );
+            }
+        }
+
+        // without rat the weights are ignored
+        let source = format!("{vertices}cstype bezier\ndeg 2\ncurv 0 1 1 2 3\nend\n");
+        let curve = &parse_obj_curves(&source).unwrap().curves[0].curve;
+
+        assert_close(curve.get_point(0.5), Vec3::new(0.75, 0.75, 0.0));
+    }
+
+    #[test]
+    fn knots_must_match_degree_and_control_points() {
+        let source =
+            format!("{SQUARE}cstype bspline\ndeg 2\ncurv 0 1 1 2 3 4\nparm u 0 0 1 1\nend\n");
+
+        match parse_obj_curves(&source) {
+            Err(ObjCurveError::Parse { line, message }) => {
+                // reported where the curve starts
+                assert_eq!(line, 7);
+                assert!(message.contains("knot vector"), "{message}");
+            }
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn unsupported_curve_type_is_an_error() {
+        let source = format!("{SQUARE}cstype taylor\n");
+
+        assert!(matches!(
+            parse_obj_curves(&source),
+            Err(ObjCurveError::Parse { line: 5, .. })
+        ));
+    }
+}
@@ -0,0 +1,106 @@
+use three_d::{InnerSpace, Mat4, SquareMatrix, Vec3};
+
+use crate::{bvh::Bvh, model::LoadedModel, ray::Ray};
+
+/// What was hit by a pick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PickTarget {
+    Part { model: usize, part: usize },
+    Tube,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PickHit {
+    pub target: PickTarget,
+    /// Index of the triangle in the mesh that was hit.
+    pub triangle: usize,
+    /// Vertex indices of that triangle.
+    pub vertices: [usize; 3],
+    /// Weights of the triangle vertices at the hit point.
+    pub barycentric: Vec3,
+    /// The hit point in the scene.
+    pub point: Vec3,
+    /// Distance from the ray origin to `point`.
+    pub distance: f32,
+}
+
+impl PickHit {
+    /// The triangle vertex closest to the hit point.
+    pub fn nearest_vertex(&self) -> usize {
+        let weights = self.barycentric;
+        let corner = if weights.x >= weights.y && weights.x >= weights.z {
+            0
+        } else if weights.y >= weights.z {
+            1
+        } else {
+            2
+        };
+
+        self.vertices[corner]
+    }
+
+    /// The triangle edge closest to the hit point, the one across from the smallest weight.
+    pub fn nearest_edge(&self) -> (usize, usize) {
+        let weights = self.barycentric;
+        let [a, b, c] = self.vertices;
+
+        if weights.x <= weights.y && weights.x <= weights.z {
+            (b, c)
+        } else if weights.y <= weights.z {
+            (c, a)
+        } else {
+            (a, b)
+        }
+    }
+}
+
+/// Intersects a mesh placed with `transformation`, measuring the distance in the scene.
+pub fn pick_mesh(
+    ray: &Ray,
+    bvh: &Bvh,
+    transformation: Mat4,
+    target: PickTarget,
+) -> Option<PickHit> {
+    let hit = bvh.intersect(&ray.to_local(transformation)?)?;
+
+    let [a, b, c] = hit.vertices.map(|vertex| bvh.positions()[vertex]);
+    let local = a * hit.barycentric.x + b * hit.barycentric.y + c * hit.barycentric.z;
+    let point = (transformation * local.extend(1.0)).truncate();
+
+    Some(PickHit {
+        target,
+        triangle: hit.triangle,
+        vertices: hit.vertices,
+        barycentric: hit.barycentric,
+        point,
+        distance: (point - ray.origin).magnitude(),
+    })
+}
+
+/// The closest visible model part, or the tube if one is given, hit by the ray.
+pub fn pick(ray: &Ray, models: &[LoadedModel], tube: Option<&Bvh>) -> Option<PickHit> {
+    let mut closest = tube.and_then(|bvh| pick_mesh(ray, bvh, Mat4::identity(), PickTarget::Tube));
+
+    for (model_index, model) in models.iter().enumerate() {
+        model
+            .root
+            .for_each_visible_part(&model.parts, &mut |part_index| {
+                let target = PickTarget::Part {
+                    model: model_index,
+                    part: part_index,
+                };
+
+                if let Some(hit) = pick_mesh(
+                    ray,
+                    &model.parts[part_index].bvh,
+                    model.part_transformation(part_index),
+                    target,
+                ) && closest.is_none_or(|closest| hit.distance < closest.distance)
+                {
+                    closest = Some(hit);
+                }
+            });
+    }
+
+    closest
+}
@@ -0,0 +1,249 @@
+use std::{fmt, fs, path::Path};
+
+use three_d::Vec3;
+
+use crate::curves::CatmullRomCurve;
+
+/// Points read from a CSV or XYZ file, one `x y z [radius]` entry per line.
+pub struct PointList {
+    pub points: Vec<Vec3>,
+    pub radii: Option<Vec<f32>>,
+}
+
+#[derive(Debug)]
+pub enum PointsError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+    NotEnoughPoints(usize),
+}
+
+impl fmt::Display for PointsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PointsError::Io(error) => write!(f, "{error}"),
+            PointsError::Parse { line, message } => write!(f, "line {line}: {message}"),
+            PointsError::NotEnoughPoints(count) => {
+                write!(f, "expected at least 2 points, found {count}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PointsError {}
+
+impl From<std::io::Error> for PointsError {
+    fn from(error: std::io::Error) -> Self {
+        PointsError::Io(error)
+    }
+}
+
+impl PointList {
+    /// A curve through the points, scaling the tube radius by each radius
+    /// relative to [`mean_radius`](Self::mean_radius).
+    pub fn to_catmull_rom(&self, closed: bool) -> CatmullRomCurve {
+        let mut curve = CatmullRomCurve::new(self.points.clone(), closed);
+
+        if let (Some(radii), Some(mean)) = (&self.radii, self.mean_radius()) {
+            curve.radius_scales = Some(radii.iter().map(|radius| radius / mean).collect());
+        }
+
+        curve
+    }
+
+    /// Returns the average of the radius column, if the file had one and it is
+    /// above zero, as a tube of radius zero would not show.
+    pub fn mean_radius(&self) -> Option<f32> {
+        let radii = self.radii.as_ref()?;
+        let mean = radii.iter().sum::<f32>() / radii.len() as f32;
+
+        (mean > 0.0).then_some(mean)
+    }
+}
+
+/// Returns true if the path has an extension handled by [`load_points`].
+pub fn is_point_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("csv") || extension.eq_ignore_ascii_case("xyz")
+        })
+}
+
+pub fn load_points(path: &Path) -> Result<PointList, PointsError> {
+    let source = fs::read_to_string(path)?;
+    parse_points(&source)
+}
+
+/// Parses whitespace or comma separated point lists.
+///
+/// Empty lines and lines starting with `#` are skipped, and a non-numeric first
+/// line is treated as a CSV header. Either every point has a radius column or none
+/// does, and radii may not be negative.
+pub fn parse_points(source: &str) -> Result<PointList, PointsError> {
+    let mut points = Vec::new();
+    let mut radii = Vec::new();
+    let mut columns = None;
+
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let values: Result<Vec<f32>, _> = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|value| !value.is_empty())
+            .map(str::parse::<f32>)
+            .collect();
+
+        let values = match values {
+            Ok(values) => values,
+            // header
+            Err(_) if columns.is_none() && points.is_empty() => {
+                columns = Some(0);
+                continue;
+            }
+            Err(error) => {
+                return Err(PointsError::Parse {
+                    line: line_number,
+                    message: error.to_string(),
+                });
+            }
+        };
+
+        if values.iter().any(|value| !value.is_finite()) {
+            return Err(PointsError::Parse {
+                line: line_number,
+                message: "expected finite numbers".to_string(),
+            });
+        }
+
+        if values.len() != 3 && values.len() != 4 {
+            return Err(PointsError::Parse {
+                line: line_number,
+                message: format!("expected 3 or 4 columns, found {}", values.len()),
+            });
+        }
+
+        match columns {
+            Some(count) if count != 0 && count != values.len() => {
+                return Err(PointsError::Parse {
+                    line: line_number,
+                    message: format!("expected {} columns, found {}", count, values.len()),
+                });
+            }
+            _ => columns = Some(values.len()),
+        }
+
+        points.push(Vec3::new(values[0], values[1], values[2]));
+
+        if let Some(&radius) = values.get(3) {
+            if radius < 0.0 {
+                return Err(PointsError::Parse {
+                    line: line_number,
+                    message: format!("negative radius {radius}"),
+                });
+            }
+
+            radii.push(radius);
+        }
+    }
+
+    if points.len() < 2 {
+        return Err(PointsError::NotEnoughPoints(points.len()));
+    }
+
+    Ok(PointList {
+        points,
+        radii: (!radii.is_empty()).then_some(radii),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error_line(source: &str) -> Option<usize> {
+        match parse_points(source) {
+            Err(PointsError::Parse { line, .. }) => Some(line),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn header_and_comments_are_skipped() {
+        let source = "x,y,z\n# start\n0,0,0\n\n  # end\n1,2,3\n";
+        let list = parse_points(source).unwrap();
+
+        assert_eq!(
+            list.points,
+            vec![Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 2.0, 3.0)]
+        );
+        assert!(list.radii.is_none());
+        assert_eq!(list.mean_radius(), None);
+    }
+
+    #[test]
+    fn whitespace_separated_with_radius() {
+        let list = parse_points("0 0 0 0.5\n1\t0 0 1.5\n2, 0, 0, 1\n").unwrap();
+
+        assert_eq!(list.points.len(), 3);
+        assert_eq!(list.radii, Some(vec![0.5, 1.5, 1.0]));
+        assert_eq!(list.mean_radius(), Some(1.0));
+    }
+
+    #[test]
+    fn radius_scales_are_relative_to_the_mean() {
+        let list = parse_points("0 0 0 1\n1 0 0 3\n").unwrap();
+        let curve = list.to_catmull_rom(false);
+
+        assert_eq!(curve.radius_scales, Some(vec![0.5, 1.5]));
+    }
+
+    #[test]
+    fn zero_radii_have_no_mean() {
+        let list = parse_points("0 0 0 0\n1 0 0 0\n").unwrap();
+
+        assert_eq!(list.mean_radius(), None);
+        assert!(list.to_catmull_rom(false).radius_scales.is_none());
+    }
+
+    #[test]
+    fn negative_radius_is_an_error() {
+        assert_eq!(parse_error_line("0 0 0 1\n1 0 0 -1\n"), Some(2));
+    }
+
+    #[test]
+    fn bad_numbers_are_errors() {
+        assert_eq!(parse_error_line("0 0 0\n1 zero 0\n"), Some(2));
+        assert_eq!(parse_error_line("0 0 0\n1 NaN 0\n"), Some(2));
+        assert_eq!(parse_error_line("0 0 0\ninf 0 0\n"), Some(2));
+    }
+
+    #[test]
+    fn a_second_header_is_an_error() {
+        assert_eq!(parse_error_line("x,y,z\n0,0,0\nx,y,z\n"), Some(3));
+    }
+
+    #[test]
+    fn column_counts_must_match() {
+        assert_eq!(parse_error_line("0 0 0\n1 0 0 1\n"), Some(2));
+        assert_eq!(parse_error_line("0 0 0 1\n1 0 0\n"), Some(2));
+        assert_eq!(parse_error_line("0 0\n1 0\n"), Some(1));
+        assert_eq!(parse_error_line("0 0 0 0 0\n"), Some(1));
+    }
+
+    #[test]
+    fn needs_two_points() {
+        assert!(matches!(
+            parse_points("x,y,z\n0,0,0\n"),
+            Err(PointsError::NotEnoughPoints(1))
+        ));
+        assert!(matches!(
+            parse_points(""),
+            Err(PointsError::NotEnoughPoints(0))
+        ));
+    }
+}
@@ -0,0 +1,632 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{Value, json};
+use three_d::{Srgba, Vec3, vec3};
+
+use crate::{
+    lights::{LightSettings, LightType, default_lights},
+    model::ModelTransform,
+    spline::{Spline, SplineType},
+    tube::TubeParameters,
+    view::{CameraPose, Projection},
+};
+
+/// Version written to new project files, older ones are still read.
+const VERSION: u64 = 1;
+
+/// Everything needed to restore a viewer session, saved as JSON.
+///
+/// Paths are stored relative to the project file when they are below it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Project {
+    pub models: Vec<ProjectModel>,
+    pub curve: ProjectCurve,
+    pub tube_parameters: TubeParameters,
+    pub camera: CameraPose,
+    pub projection: Projection,
+    pub lights: Vec<LightSettings>,
+    pub display: DisplayOptions,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProjectModel {
+    pub path: PathBuf,
+    pub name: String,
+    pub visible: bool,
+    pub transform: ModelTransform,
+}
+
+/// Where the path of the tube comes from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProjectCurve {
+    /// The built in sine curve.
+    Default,
+    /// A point or OBJ file, read again when the project is opened.
+    File(PathBuf),
+    Spline(Spline),
+}
+
+/// The display toggles of the side panel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DisplayOptions {
+    pub axes: bool,
+    pub debug_arrow: bool,
+    pub debug_sphere: bool,
+    pub sphere_position: Vec3,
+    pub tube: bool,
+    pub tube_vertices: bool,
+    pub tube_edges: bool,
+    pub tube_transparent: bool,
+    pub tube_arrows: bool,
+}
+
+#[derive(Debug)]
+pub enum ProjectError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectError::Io(error) => write!(f, "{error}"),
+            ProjectError::Json(error) => write!(f, "{error}"),
+            ProjectError::Invalid(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {}
+
+impl From<io::Error> for ProjectError {
+    fn from(error: io::Error) -> Self {
+        ProjectError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for ProjectError {
+    fn from(error: serde_json::Error) -> Self {
+        ProjectError::Json(error)
+    }
+}
+
+pub fn save_project(path: &Path, project: &Project) -> io::Result<()> {
+    let base = path.parent().unwrap_or(Path::new(""));
+
+    let models: Vec<Value> = project
+        .models
+        .iter()
+        .map(|model| {
+            json!({
+                "path": relative_path(base, &model.path),
+                "name": model.name,
+                "visible": model.visible,
+                "position": vec3_json(model.transform.position),
+                "rotation": vec3_json(model.transform.rotation),
+                "scale": vec3_json(model.transform.scale),
+            })
+        })
+        .collect();
+
+    let curve = match &project.curve {
+        ProjectCurve::Default => json!({ "type": "default" }),
+        ProjectCurve::File(file) => json!({ "type": "file", "path": relative_path(base, file) }),
+        ProjectCurve::Spline(spline) => json!({
+            "type": "spline",
+            "spline_type": spline_type_key(spline.spline_type),
+            "points": spline.points.iter().map(|&point| vec3_json(point)).collect::<Vec<_>>(),
+        }),
+    };
+
+    let tube = &project.tube_parameters;
+    let camera = &project.camera;
+    let lights: Vec<Value> = project
+        .lights
+        .iter()
+        .map(|light| {
+            json!({
+                "type": light_type_key(light.light_type),
+                "color": color_json(light.color),
+                "intensity": light.intensity,
+                "position": vec3_json(light.position),
+                "direction": vec3_json(light.direction),
+                "cutoff": light.cutoff,
+                "shadows": light.shadows,
+            })
+        })
+        .collect();
+    let display = &project.display;
+
+    let value = json!({
+        "version": VERSION,
+        "models": models,
+        "curve": curve,
+        "tube": {
+            "tubular_segments": tube.tubular_segments,
+            "radial_segments": tube.radial_segments,
+            "radius": tube.radius,
+            "closed": tube.closed,
+        },
+        "camera": {
+            "position": vec3_json(camera.position),
+            "target": vec3_json(camera.target),
+            "up": vec3_json(camera.up),
+            "orthographic": project.projection == Projection::Orthographic,
+        },
+        "lights": lights,
+        "display": {
+            "axes": display.axes,
+            "debug_arrow": display.debug_arrow,
+            "debug_sphere": display.debug_sphere,
+            "sphere_position": vec3_json(display.sphere_position),
+            "tube": display.tube,
+            "tube_vertices": display.tube_vertices,
+            "tube_edges": display.tube_edges,
+            "tube_transparent": display.tube_transparent,
+            "tube_arrows": display.tube_arrows,
+        },
+    });
+
+    fs::write(path, serde_json::to_vec_pretty(&value)?)
+}
+
+/// Reads a project, resolving relative paths against its directory.
+pub fn load_project(path: &Path) -> Result<Project, ProjectError> {
+    let value: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    let base = path.parent().unwrap_or(Path::new(""));
+
+    if let Some(version) = value.get("version").and_then(Value::as_u64)
+        && version > VERSION
+    {
+        return Err(ProjectError::Invalid(format!(
+            "version {version} is newer than this viewer supports"
+        )));
+    }
+
+    let models = value
+        .get("models")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .map(|model| {
+            let path = model
+                .get("path")
+                .and_then(Value::as_str)
+                .ok_or_else(|| invalid("a model has no path"))?;
+            let defaults = ModelTransform::default();
+
+            Ok(ProjectModel {
+                path: base.join(path),
+                name: model
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                visible: bool_field(model, "visible", true),
+                transform: ModelTransform {
+                    position: vec3_field(model, "position", defaults.position)?,
+                    rotation: vec3_field(model, "rotation", defaults.rotation)?,
+                    scale: vec3_field(model, "scale", defaults.scale)?,
+                },
+            })
+        })
+        .collect::<Result<Vec<_>, ProjectError>>()?;
+
+    let curve = match value.get("curve") {
+        None => ProjectCurve::Default,
+        Some(curve) => match curve.get("type").and_then(Value::as_str) {
+            Some("default") => ProjectCurve::Default,
+            Some("file") => ProjectCurve::File(
+                base.join(
+                    curve
+                        .get("path")
+                        .and_then(Value::as_str)
+                        .ok_or_else(|| invalid("the curve file has no path"))?,
+                ),
+            ),
+            Some("spline") => {
+                let spline_type = curve
+                    .get("spline_type")
+                    .and_then(Value::as_str)
+                    .and_then(spline_type_from_key)
+                    .ok_or_else(|| invalid("unknown spline type"))?;
+                let points = curve
+                    .get("points")
+                    .and_then(Value::as_array)
+                    .ok_or_else(|| invalid("the spline has no points"))?
+                    .iter()
+                    .map(|point| vec3_value(point).ok_or_else(|| invalid("invalid spline point")))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                ProjectCurve::Spline(Spline::new(points, spline_type))
+            }
+            _ => return Err(invalid("unknown curve type")),
+        },
+    };
+
+    let empty = json!({});
+    let tube = value.get("tube").unwrap_or(&empty);
+    let tube_defaults = TubeParameters::default();
+    let tube_parameters = TubeParameters {
+        tubular_segments: segments_field(tube, "tubular_segments", tube_defaults.tubular_segments)?,
+        radial_segments: segments_field(tube, "radial_segments", tube_defaults.radial_segments)?,
+        radius: positive_f32_field(tube, "radius", tube_defaults.radius)?,
+        closed: bool_field(tube, "closed", tube_defaults.closed),
+    };
+
+    let camera = value
+        .get("camera")
+        .ok_or_else(|| invalid("the camera is missing"))?;
+    let projection = if bool_field(camera, "orthographic", false) {
+        Projection::Orthographic
+    } else {
+        Projection::Perspective
+    };
+    let camera = CameraPose {
+        position: vec3_field(camera, "position", vec3(0.0, 0.0, 2.0))?,
+        target: vec3_field(camera, "target", vec3(0.0, 0.0, 0.0))?,
+        up: vec3_field(camera, "up", vec3(0.0, 1.0, 0.0))?,
+    };
+
+    let lights = match value.get("lights") {
+        None => default_lights(),
+        Some(Value::Array(lights)) => lights
+            .iter()
+            .map(|light| {
+                let light_type = light
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .and_then(light_type_from_key)
+                    .ok_or_else(|| invalid("unknown light type"))?;
+                let defaults = LightSettings::new(light_type);
+
+                Ok(LightSettings {
+                    light_type,
+                    color: color_field(light, "color", defaults.color)?,
+                    intensity: checked_f32_field(light, "intensity", defaults.intensity, |v| {
+                        v >= 0.0
+                    })?,
+                    position: vec3_field(light, "position", defaults.position)?,
+                    direction: vec3_field(light, "direction", defaults.direction)?,
+                    cutoff: checked_f32_field(light, "cutoff", defaults.cutoff, |v| {
+                        v > 0.0 && v <= 90.0
+                    })?,
+                    shadows: bool_field(light, "shadows", defaults.shadows),
+                })
+            })
+            .collect::<Result<Vec<_>, ProjectError>>()?,
+        Some(_) => return Err(invalid("invalid lights")),
+    };
+
+    let display = value.get("display").unwrap_or(&empty);
+    let display = DisplayOptions {
+        axes: bool_field(display, "axes", false),
+        debug_arrow: bool_field(display, "debug_arrow", false),
+        debug_sphere: bool_field(display, "debug_sphere", false),
+        sphere_position: vec3_field(display, "sphere_position", vec3(0.5, 0.3, 0.35))?,
+        tube: bool_field(display, "tube", false),
+        tube_vertices: bool_field(display, "tube_vertices", false),
+        tube_edges: bool_field(display, "tube_edges", false),
+        tube_transparent: bool_field(display, "tube_transparent", false),
+        tube_arrows: bool_field(display, "tube_arrows", false),
+    };
+
+    Ok(Project {
+        models,
+        curve,
+        tube_parameters,
+        camera,
+        projection,
+        lights,
+        display,
+    })
+}
+
+fn invalid(message: &str) -> ProjectError {
+    ProjectError::Invalid(message.to_string())
+}
+
+fn relative_path(base: &Path, path: &Path) -> String {
+    path.strip_prefix(base)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+fn spline_type_key(spline_type: SplineType) -> &'static str {
+    match spline_type {
+        SplineType::Bezier => "bezier",
+        SplineType::CatmullRom => "catmull_rom",
+        SplineType::BSpline => "b_spline",
+    }
+}
+
+fn spline_type_from_key(key: &str) -> Option<SplineType> {
+    SplineType::ALL
+        .into_iter()
+        .find(|&spline_type| spline_type_key(spline_type) == key)
+}
+
+fn light_type_key(light_type: LightType) -> &'static str {
+    match light_type {
+        LightType::Ambient => "ambient",
+        LightType::Directional => "directional",
+        LightType::Point => "point",
+        LightType::Spot => "spot",
+    }
+}
+
+fn light_type_from_key(key: &str) -> Option<LightType> {
+    LightType::ALL
+        .into_iter()
+        .find(|&light_type| light_type_key(light_type) == key)
+}
+
+fn vec3_json(v: Vec3) -> Value {
+    json!([v.x, v.y, v.z])
+}
+
+fn color_json(color: Srgba) -> Value {
+    json!([color.r, color.g, color.b, color.a])
+}
+
+fn vec3_value(value: &Value) -> Option<Vec3> {
+    let array = value.as_array().filter(|array| array.len() == 3)?;
+    let component = |index: usize| array[index].as_f64().map(|v| v as f32);
+
+    Some(vec3(component(0)?, component(1)?, component(2)?))
+}
+
+/// A missing field gives `default`, one of the wrong shape an error.
+fn vec3_field(value: &Value, key: &str, default: Vec3) -> Result<Vec3, ProjectError> {
+    match value.get(key) {
+        None => Ok(default),
+        Some(field) => vec3_value(field).ok_or_else(|| invalid(&format!("invalid {key}"))),
+    }
+}
+
+fn color_field(value: &Value, key: &str, default: Srgba) -> Result<Srgba, ProjectError> {
+    let Some(field) = value.get(key) else {
+        return Ok(default);
+    };
+
+    let channels = field
+        .as_array()
+        .filter(|array| array.len() == 4)
+        .and_then(|array| {
+            array
+                .iter()
+                .map(|channel| channel.as_u64().and_then(|v| u8::try_from(v).ok()))
+                .collect::<Option<Vec<u8>>>()
+        })
+        .ok_or_else(|| invalid(&format!("invalid {key}")))?;
+
+    Ok(Srgba::new(
+        channels[0],
+        channels[1],
+        channels[2],
+        channels[3],
+    ))
+}
+
+fn bool_field(value: &Value, key: &str, default: bool) -> bool {
+    value.get(key).and_then(Value::as_bool).unwrap_or(default)
+}
+
+/// A count of at least one, a missing field gives `default`.
+fn segments_field(value: &Value, key: &str, default: usize) -> Result<usize, ProjectError> {
+    match value.get(key) {
+        None => Ok(default),
+        Some(field) => field
+            .as_u64()
+            .filter(|&v| v > 0)
+            .map(|v| v as usize)
+            .ok_or_else(|| invalid(&format!("invalid {key}"))),
+    }
+}
+
+/// A number above zero, a missing field gives `default`.
+fn positive_f32_field(value: &Value, key: &str, default: f32) -> Result<f32, ProjectError> {
+    checked_f32_field(value, key, default, |v| v > 0.0)
+}
+
+/// A finite number `valid` accepts, a missing field gives `default`.
+fn checked_f32_field(
+    value: &Value,
+    key: &str,
+    default: f32,
+    valid: impl Fn(f32) -> bool,
+) -> Result<f32, ProjectError> {
+    match value.get(key) {
+        None => Ok(default),
+        Some(field) => field
+            .as_f64()
+            .map(|v| v as f32)
+            .filter(|&v| v.is_finite() && valid(v))
+            .ok_or_else(|| invalid(&format!("invalid {key}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A scratch directory for one test, emptied first.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rene-project-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn project(dir: &Path) -> Project {
+        Project {
+            models: vec![ProjectModel {
+                path: dir.join("meshes").join("teapot.obj"),
+                name: "Teapot".to_string(),
+                visible: false,
+                transform: ModelTransform {
+                    position: vec3(1.0, -2.0, 0.5),
+                    rotation: vec3(0.0, 90.0, 0.0),
+                    scale: vec3(2.0, 2.0, 2.0),
+                },
+            }],
+            curve: ProjectCurve::Spline(Spline::new(
+                vec![
+                    vec3(0.0, 0.0, 0.0),
+                    vec3(1.0, 1.0, 0.0),
+                    vec3(2.0, 0.0, 1.0),
+                ],
+                SplineType::BSpline,
+            )),
+            tube_parameters: TubeParameters {
+                tubular_segments: 40,
+                radial_segments: 12,
+                radius: 0.05,
+                closed: true,
+            },
+            camera: CameraPose {
+                position: vec3(3.0, 2.0, 1.0),
+                target: vec3(0.0, 0.5, 0.0),
+                up: vec3(0.0, 1.0, 0.0),
+            },
+            projection: Projection::Orthographic,
+            lights: vec![
+                LightSettings::new(LightType::Ambient),
+                LightSettings {
+                    color: Srgba::new(10, 20, 30, 255),
+                    cutoff: 20.0,
+                    shadows: true,
+                    ..LightSettings::new(LightType::Spot)
+                },
+            ],
+            display: DisplayOptions {
+                axes: true,
+                debug_arrow: false,
+                debug_sphere: true,
+                sphere_position: vec3(0.25, 0.5, 0.75),
+                tube: true,
+                tube_vertices: false,
+                tube_edges: true,
+                tube_transparent: false,
+                tube_arrows: true,
+            },
+        }
+    }
+
+    #[test]
+    fn save_then_load_restores_the_project() {
+        let dir = test_dir("round-trip");
+        let path = dir.join("scene.json");
+        let project = project(&dir);
+
+        save_project(&path, &project).unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+
+        // paths below the project are stored relative to it
+        assert!(!saved.contains(dir.to_string_lossy().as_ref()));
+        assert_eq!(load_project(&path).unwrap(), project);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn lights_must_be_a_list() {
+        let dir = test_dir("lights");
+        let path = dir.join("scene.json");
+
+        for lights in [
+            "3",
+            r#""bright""#,
+            "true",
+            "null",
+            r#"{ "ambient_intensity": 0.25 }"#,
+        ] {
+            fs::write(
+                &path,
+                format!(r#"{{ "version": 1, "camera": {{}}, "lights": {lights} }}"#),
+            )
+            .unwrap();
+
+            assert!(
+                matches!(load_project(&path), Err(ProjectError::Invalid(_))),
+                "{lights}"
+            );
+        }
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn light_intensity_and_cutoff_must_be_in_range() {
+        let dir = test_dir("light-values");
+        let path = dir.join("scene.json");
+
+        for light in [
+            r#"{ "type": "point", "intensity": -1 }"#,
+            r#"{ "type": "spot", "cutoff": 0 }"#,
+            r#"{ "type": "spot", "cutoff": -20 }"#,
+            r#"{ "type": "spot", "cutoff": 90.5 }"#,
+            r#"{ "type": "spot", "cutoff": "wide" }"#,
+        ] {
+            fs::write(
+                &path,
+                format!(r#"{{ "version": 1, "camera": {{}}, "lights": [{light}] }}"#),
+            )
+            .unwrap();
+
+            assert!(
+                matches!(load_project(&path), Err(ProjectError::Invalid(_))),
+                "{light}"
+            );
+        }
+
+        // the limits themselves are fine
+        fs::write(
+            &path,
+            r#"{ "version": 1, "camera": {}, "lights": [
+                { "type": "ambient", "intensity": 0 },
+                { "type": "spot", "cutoff": 90 }
+            ] }"#,
+        )
+        .unwrap();
+
+        let lights = load_project(&path).unwrap().lights;
+        assert_eq!(lights[0].intensity, 0.0);
+        assert_eq!(lights[1].cutoff, 90.0);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn tube_parameters_must_be_positive() {
+        let dir = test_dir("tube");
+        let path = dir.join("scene.json");
+
+        for tube in [
+            r#"{ "tubular_segments": 0 }"#,
+            r#"{ "radial_segments": -3 }"#,
+            r#"{ "radius": 0 }"#,
+            r#"{ "radius": -0.5 }"#,
+            r#"{ "radius": "wide" }"#,
+        ] {
+            fs::write(
+                &path,
+                format!(r#"{{ "version": 1, "camera": {{}}, "tube": {tube} }}"#),
+            )
+            .unwrap();
+
+            assert!(
+                matches!(load_project(&path), Err(ProjectError::Invalid(_))),
+                "{tube}"
+            );
+        }
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
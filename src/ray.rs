@@ -0,0 +1,120 @@
+use three_d::{
+    AxisAlignedBoundingBox, Camera, InnerSpace, Mat4, PhysicalPoint, SquareMatrix, Vec3,
+};
+
+/// A half line, `direction` is kept normalized.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    /// The ray through a pixel of the camera viewport, in physical pixels from the bottom left.
+    pub fn from_pixel(camera: &Camera, pixel: PhysicalPoint) -> Self {
+        Self::new(
+            camera.position_at_pixel(pixel),
+            camera.view_direction_at_pixel(pixel),
+        )
+    }
+
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.origin + self.direction * t
+    }
+
+    /// The ray in the space `transformation` maps from, `None` if it cannot be inverted.
+    pub fn to_local(&self, transformation: Mat4) -> Option<Self> {
+        let inverse = transformation.invert()?;
+
+        Some(Self::new(
+            (inverse * self.origin.extend(1.0)).truncate(),
+            (inverse * self.direction.extend(0.0)).truncate(),
+        ))
+    }
+
+    /// Distance along the ray to where it enters the box, zero when it starts inside.
+    pub fn intersect_aabb(&self, aabb: &AxisAlignedBoundingBox) -> Option<f32> {
+        if aabb.is_empty() {
+            return None;
+        }
+
+        let (min, max) = (aabb.min(), aabb.max());
+        let mut near = 0.0f32;
+        let mut far = f32::INFINITY;
+
+        for axis in 0..3 {
+            // parallel to the slab, where `0 * inf` would give nan for rays in its planes
+            if self.direction[axis] == 0.0 {
+                if self.origin[axis] < min[axis] || self.origin[axis] > max[axis] {
+                    return None;
+                }
+
+                continue;
+            }
+
+            let inverse = 1.0 / self.direction[axis];
+            let t1 = (min[axis] - self.origin[axis]) * inverse;
+            let t2 = (max[axis] - self.origin[axis]) * inverse;
+
+            near = near.max(t1.min(t2));
+            far = far.min(t1.max(t2));
+        }
+
+        (near <= far).then_some(near)
+    }
+
+    /// Distance along the ray to the plane through `point` with `normal`, if
+    /// the plane is in front of the ray and not parallel to it.
+    pub fn intersect_plane(&self, point: Vec3, normal: Vec3) -> Option<f32> {
+        let denominator = self.direction.dot(normal);
+
+        if denominator.abs() < 1e-6 {
+            return None;
+        }
+
+        let t = (point - self.origin).dot(normal) / denominator;
+        (t >= 0.0).then_some(t)
+    }
+
+    /// Parameters of the closest points between the ray and the line through
+    /// `point` along `direction`, as `(t on the ray, s on the line)`.
+    /// Parallel lines give `None`.
+    pub fn closest_to_line(&self, point: Vec3, direction: Vec3) -> Option<(f32, f32)> {
+        let w = self.origin - point;
+        let a = self.direction.dot(self.direction);
+        let b = self.direction.dot(direction);
+        let c = direction.dot(direction);
+        let d = self.direction.dot(w);
+        let e = direction.dot(w);
+
+        let denominator = a * c - b * b;
+
+        if denominator.abs() < 1e-6 {
+            return None;
+        }
+
+        Some(((b * e - c * d) / denominator, (a * e - b * d) / denominator))
+    }
+
+    /// Shortest distance between the ray and the segment from `start` to `end`.
+    pub fn distance_to_segment(&self, start: Vec3, end: Vec3) -> f32 {
+        let direction = end - start;
+
+        let s = self
+            .closest_to_line(start, direction)
+            .map_or(0.0, |(_, s)| s.clamp(0.0, 1.0));
+
+        let on_segment = start + direction * s;
+        // the closest point on the ray to the clamped point on the segment
+        let t = (on_segment - self.origin).dot(self.direction).max(0.0);
+
+        (self.at(t) - on_segment).magnitude()
+    }
+}
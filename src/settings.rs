@@ -0,0 +1,54 @@
+use std::{env, fs, io, path::PathBuf};
+
+use serde_json::{Value, json};
+
+/// Viewer preferences kept between runs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewerSettings {
+    /// Logical size of the window when it was last closed.
+    pub window_size: (u32, u32),
+    pub fullscreen: bool,
+}
+
+/// `viewer.json` in the per-user configuration directory, if one can be found.
+pub fn settings_path() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_dir.join("rene").join("viewer.json"))
+}
+
+/// Reads the saved settings, a missing or unreadable file gives `None`.
+pub fn load_settings() -> Option<ViewerSettings> {
+    let source = fs::read_to_string(settings_path()?).ok()?;
+    let value: Value = serde_json::from_str(&source).ok()?;
+
+    let size = value.get("window_size")?.as_array()?;
+    let dimension = |index: usize| size.get(index)?.as_u64().map(|v| v as u32);
+
+    Some(ViewerSettings {
+        window_size: (dimension(0)?, dimension(1)?),
+        fullscreen: value
+            .get("fullscreen")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+    })
+}
+
+pub fn save_settings(settings: &ViewerSettings) -> io::Result<()> {
+    let path = settings_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no configuration directory"))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let value = json!({
+        "window_size": [settings.window_size.0, settings.window_size.1],
+        "fullscreen": settings.fullscreen,
+    });
+
+    fs::write(path, serde_json::to_vec_pretty(&value)?)
+}
@@ -0,0 +1,135 @@
+use std::{
+    f32::consts::TAU,
+    fs::File,
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use three_d::{
+    Camera, ClearState, Context, CpuTexture, DepthTexture2D, InnerSpace, Interpolation, Mat3, Rad,
+    RenderTarget, Texture2D, TextureData, Vec3, Wrapping,
+};
+
+/// Renders into an offscreen color and depth target of the given size and
+/// reads the result back, top row first.
+pub fn render_offscreen(
+    context: &Context,
+    width: u32,
+    height: u32,
+    clear_state: ClearState,
+    render: impl FnOnce(&RenderTarget),
+) -> CpuTexture {
+    let mut color = Texture2D::new_empty::<[u8; 4]>(
+        context,
+        width,
+        height,
+        Interpolation::Nearest,
+        Interpolation::Nearest,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    let mut depth = DepthTexture2D::new::<f32>(
+        context,
+        width,
+        height,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+
+    let target = RenderTarget::new(color.as_color_target(None), depth.as_depth_target());
+    target.clear(clear_state);
+    render(&target);
+
+    CpuTexture {
+        data: TextureData::RgbaU8(target.read_color()),
+        width,
+        height,
+        ..Default::default()
+    }
+}
+
+/// Writes an rgba texture as a PNG file.
+pub fn save_png(path: &Path, texture: &CpuTexture) -> Result<(), three_d_asset::Error> {
+    use three_d_asset::io::Serialize;
+
+    three_d_asset::io::save(&texture.serialize(path)?)
+}
+
+/// The camera for frame `index` of a turntable with `frames` frames, rotated
+/// about its up direction around `target`.
+pub fn turntable_camera(camera: &Camera, target: Vec3, index: usize, frames: usize) -> Camera {
+    let up = camera.up().normalize();
+    let rotation = Mat3::from_axis_angle(up, Rad(TAU * index as f32 / frames as f32));
+
+    let mut camera = camera.clone();
+    let position = target + rotation * (camera.position() - target);
+    camera.set_view(position, target, up);
+    camera
+}
+
+/// Numbers an image path for a sequence, `render.png` becomes `render_0007.png`.
+pub fn sequence_path(path: &Path, index: usize) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "frame".to_string());
+
+    path.with_file_name(format!("{stem}_{index:04}.png"))
+}
+
+/// Writes rgba textures of the same size as the frames of a looping animated GIF.
+pub struct GifWriter {
+    encoder: gif::Encoder<BufWriter<File>>,
+    width: u16,
+    height: u16,
+    delay: u16,
+}
+
+impl GifWriter {
+    pub fn create(
+        path: &Path,
+        width: u32,
+        height: u32,
+        frame_delay: Duration,
+    ) -> Result<Self, gif::EncodingError> {
+        // the format limits sizes to 16 bits
+        let width = width.min(u16::MAX as u32) as u16;
+        let height = height.min(u16::MAX as u32) as u16;
+
+        let mut encoder =
+            gif::Encoder::new(BufWriter::new(File::create(path)?), width, height, &[])?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+
+        Ok(Self {
+            encoder,
+            width,
+            height,
+            // in hundredths of a second
+            delay: (frame_delay.as_millis() / 10).min(u16::MAX as u128) as u16,
+        })
+    }
+
+    pub fn add_frame(&mut self, texture: &CpuTexture) -> Result<(), gif::EncodingError> {
+        let TextureData::RgbaU8(pixels) = &texture.data else {
+            return Err(
+                io::Error::new(io::ErrorKind::InvalidInput, "expected 8 bit rgba pixels").into(),
+            );
+        };
+
+        let width = texture.width as usize;
+        let mut bytes = Vec::with_capacity(self.width as usize * self.height as usize * 4);
+
+        for row in pixels.chunks(width).take(self.height as usize) {
+            for pixel in row.iter().take(self.width as usize) {
+                bytes.extend_from_slice(pixel);
+            }
+        }
+
+        let mut frame = gif::Frame::from_rgba_speed(self.width, self.height, &mut bytes, 10);
+        frame.delay = self.delay;
+
+        self.encoder.write_frame(&frame)
+    }
+}
@@ -0,0 +1,180 @@
+use three_d::{InnerSpace, Vec3};
+
+use crate::{
+    curves::{BSplineCurve, CatmullRomCurve, Curve},
+    ray::Ray,
+};
+
+/// Fewest control points a spline is edited down to.
+pub const MIN_CONTROL_POINTS: usize = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplineType {
+    /// One Bézier curve with every control point, passing through the first and last.
+    Bezier,
+    /// Passes through every control point.
+    CatmullRom,
+    /// Clamped cubic B-spline, passing through the first and last control point.
+    BSpline,
+}
+
+impl SplineType {
+    pub const ALL: [SplineType; 3] = [
+        SplineType::Bezier,
+        SplineType::CatmullRom,
+        SplineType::BSpline,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SplineType::Bezier => "Bézier",
+            SplineType::CatmullRom => "Catmull-Rom",
+            SplineType::BSpline => "B-spline",
+        }
+    }
+}
+
+/// Control points of a path edited in the viewer, turned into a [`Curve`] with [`Spline::to_curve`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spline {
+    pub points: Vec<Vec3>,
+    pub spline_type: SplineType,
+}
+
+impl Spline {
+    pub fn new(points: Vec<Vec3>, spline_type: SplineType) -> Self {
+        Self {
+            points,
+            spline_type,
+        }
+    }
+
+    /// Control points sampled evenly along `curve`.
+    pub fn from_curve(curve: &dyn Curve, count: usize, spline_type: SplineType) -> Self {
+        let count = count.max(MIN_CONTROL_POINTS);
+        let points = (0..count)
+            .filter_map(|i| curve.get_point_at(i as f32 / (count - 1) as f32))
+            .collect();
+
+        Self::new(points, spline_type)
+    }
+
+    /// `closed` only applies to Catmull-Rom splines. `None` with too few control points.
+    pub fn to_curve(&self, closed: bool) -> Option<Box<dyn Curve + Send>> {
+        if self.points.len() < MIN_CONTROL_POINTS {
+            return None;
+        }
+
+        let points = self.points.clone();
+
+        Some(match self.spline_type {
+            SplineType::Bezier => Box::new(BSplineCurve::bezier(points)?),
+            SplineType::CatmullRom => Box::new(CatmullRomCurve::new(points, closed)),
+            SplineType::BSpline => {
+                let degree = (points.len() - 1).min(3);
+                Box::new(BSplineCurve::clamped(degree, points)?)
+            }
+        })
+    }
+
+    /// The point new ones go after, the selected one if there is one, else the
+    /// last one. `None` for an empty spline.
+    pub fn insertion_point(&self, selected: Option<usize>) -> Option<usize> {
+        selected
+            .filter(|&index| index < self.points.len())
+            .or(self.points.len().checked_sub(1))
+    }
+
+    /// Inserts a point halfway to the next one, or past the end after the last
+    /// one, returning its index.
+    pub fn insert_after(&mut self, index: usize) -> usize {
+        let point = match (self.points.get(index), self.points.get(index + 1)) {
+            (Some(&current), Some(&next)) => (current + next) * 0.5,
+            (Some(&current), None) if index > 0 => current * 2.0 - self.points[index - 1],
+            // a lone point has no direction to continue in
+            (Some(&current), None) => current + Vec3::new(1.0, 0.0, 0.0),
+            (None, _) => return self.push(Vec3::new(0.0, 0.0, 0.0)),
+        };
+
+        self.points.insert(index + 1, point);
+        index + 1
+    }
+
+    pub fn push(&mut self, point: Vec3) -> usize {
+        self.points.push(point);
+        self.points.len() - 1
+    }
+
+    /// Removes a control point unless that would leave too few, returning whether it did.
+    pub fn remove(&mut self, index: usize) -> bool {
+        if index >= self.points.len() || self.points.len() <= MIN_CONTROL_POINTS {
+            return false;
+        }
+
+        self.points.remove(index);
+        true
+    }
+
+    /// The control point the ray passes closest to, if within `threshold` of it.
+    pub fn pick(&self, ray: &Ray, threshold: impl Fn(Vec3) -> f32) -> Option<usize> {
+        self.points
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &point)| {
+                let t = (point - ray.origin).dot(ray.direction).max(0.0);
+                let distance = (ray.at(t) - point).magnitude();
+                (distance <= threshold(point)).then_some((index, t))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(index, _)| index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insertion_point_of_empty_spline_is_none() {
+        let spline = Spline::new(Vec::new(), SplineType::CatmullRom);
+
+        assert_eq!(spline.insertion_point(None), None);
+        assert_eq!(spline.insertion_point(Some(0)), None);
+    }
+
+    #[test]
+    fn insertion_point_prefers_selection() {
+        let spline = Spline::new(vec![Vec3::new(0.0, 0.0, 0.0); 3], SplineType::CatmullRom);
+
+        assert_eq!(spline.insertion_point(None), Some(2));
+        assert_eq!(spline.insertion_point(Some(1)), Some(1));
+        assert_eq!(spline.insertion_point(Some(3)), Some(2));
+    }
+
+    #[test]
+    fn insert_into_empty_spline() {
+        let mut spline = Spline::new(Vec::new(), SplineType::CatmullRom);
+
+        assert_eq!(spline.insert_after(0), 0);
+        assert_eq!(spline.points, vec![Vec3::new(0.0, 0.0, 0.0)]);
+    }
+
+    #[test]
+    fn insert_after_single_point_moves_away_from_it() {
+        let mut spline = Spline::new(vec![Vec3::new(1.0, 2.0, 3.0)], SplineType::CatmullRom);
+
+        assert_eq!(spline.insert_after(0), 1);
+        assert_ne!(spline.points[0], spline.points[1]);
+    }
+
+    #[test]
+    fn insert_after_last_point_continues_the_spline() {
+        let mut spline = Spline::new(
+            vec![Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0)],
+            SplineType::CatmullRom,
+        );
+
+        assert_eq!(spline.insert_after(1), 2);
+        assert_eq!(spline.points[2], Vec3::new(2.0, 0.0, 0.0));
+    }
+}
@@ -1,4 +1,4 @@
-use three_d::{InnerSpace, Vec3};
+use three_d::{CpuMesh, Indices, InnerSpace, Positions, Vec3};
 
 use crate::curves::{Curve, FrenetFrame};
 
@@ -7,14 +7,39 @@ pub struct VPair {
     pub direction: Vec3,
 }
 
+/// Everything besides the path that determines the shape of a [`Tube`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TubeParameters {
+    pub tubular_segments: usize,
+    pub radial_segments: usize,
+    pub radius: f32,
+    pub closed: bool,
+}
+
+impl Default for TubeParameters {
+    fn default() -> Self {
+        Self {
+            tubular_segments: 5,
+            radial_segments: 5,
+            radius: 0.2,
+            closed: false,
+        }
+    }
+}
+
+impl TubeParameters {
+    /// Whether both sample the path at the same points, so only the cross section differs.
+    pub fn same_path(&self, other: &Self) -> bool {
+        self.tubular_segments == other.tubular_segments && self.closed == other.closed
+    }
+}
+
 pub struct Tube {
     pub vertices: Vec<Vec3>,
     pub indices: Vec<u32>,
     pub center_points: Vec<Vec3>,
     pub normals: Vec<Vec3>,
-    pub normals_frame: Vec<Vec3>,
-    pub binormals_frame: Vec<Vec3>,
-    pub tangents_frame: Vec<Vec3>,
+    pub frame: FrenetFrame,
 }
 
 impl Tube {
@@ -49,12 +74,33 @@ impl Tube {
             indices,
             normals,
             center_points,
-            normals_frame: frame.normals,
-            binormals_frame: frame.binormals,
-            tangents_frame: frame.tangents,
+            frame,
         }
     }
 
+    pub fn from_parameters(path: &dyn Curve, parameters: &TubeParameters) -> Self {
+        Self::new(
+            path,
+            parameters.tubular_segments,
+            parameters.closed,
+            parameters.radius,
+            parameters.radial_segments,
+        )
+    }
+
+    /// The tube surface as an indexed mesh with smooth normals.
+    pub fn to_cpu_mesh(&self) -> CpuMesh {
+        let mut mesh = CpuMesh {
+            positions: Positions::F32(self.vertices.clone()),
+            indices: Indices::U32(self.indices.clone()),
+            ..Default::default()
+        };
+
+        mesh.compute_normals();
+        mesh
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn generate_buffer(
         curve: &dyn Curve,
         indices: &mut Vec<u32>,
@@ -74,9 +120,9 @@ impl Tube {
             Self::generate_segment(
                 vertices,
                 normals,
-                &point,
+                point,
                 radial_segments,
-                radius,
+                radius * curve.get_radius_scale(i as f32 / tubular_segments as f32),
                 &frames.normals[i],
                 &frames.binormals[i],
             );
@@ -91,8 +137,8 @@ impl Tube {
         point: &Vec3,
         radial_segments: usize,
         radius: f32,
-        frame_N: &Vec3,
-        frame_B: &Vec3,
+        frame_n: &Vec3,
+        frame_b: &Vec3,
     ) {
         // // generate normals and vertices for the current segment
 
@@ -106,9 +152,9 @@ impl Tube {
 
             let mut normal = Vec3::new(0., 0., 0.);
 
-            normal.x = cos * frame_N.x + sin * frame_B.x;
-            normal.y = cos * frame_N.y + sin * frame_B.y;
-            normal.z = cos * frame_N.z + sin * frame_B.z;
+            normal.x = cos * frame_n.x + sin * frame_b.x;
+            normal.y = cos * frame_n.y + sin * frame_b.y;
+            normal.z = cos * frame_n.z + sin * frame_b.z;
             normal.normalize();
             normals.push(normal);
 
@@ -0,0 +1,242 @@
+use three_d::{AxisAlignedBoundingBox, Camera, Deg, InnerSpace, Rad, Vec3, vec3};
+
+/// Vertical field of view of the perspective projection.
+pub const FIELD_OF_VIEW_Y: Deg<f32> = Deg(45.0);
+pub const Z_NEAR: f32 = 0.1;
+pub const Z_FAR: f32 = 100.0;
+
+/// Room left around framed bounds, as a factor of their radius.
+const FRAMING_MARGIN: f32 = 1.1;
+
+/// Where the camera is and what it looks at.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraPose {
+    pub position: Vec3,
+    pub target: Vec3,
+    pub up: Vec3,
+}
+
+impl CameraPose {
+    /// The pose of `camera` orbiting around `target`, which may differ from the camera target.
+    pub fn of(camera: &Camera, target: Vec3) -> Self {
+        Self {
+            position: camera.position(),
+            target,
+            up: camera.up(),
+        }
+    }
+
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.set_view(self.position, self.target, self.up);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Projection {
+    Perspective,
+    /// Keeps the scale the perspective projection has at the camera target.
+    Orthographic,
+}
+
+impl Projection {
+    pub const ALL: [Projection; 2] = [Projection::Perspective, Projection::Orthographic];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Projection::Perspective => "Perspective",
+            Projection::Orthographic => "Orthographic",
+        }
+    }
+
+    pub fn apply(&self, camera: &mut Camera) {
+        match self {
+            Projection::Perspective => {
+                camera.set_perspective_projection(FIELD_OF_VIEW_Y, Z_NEAR, Z_FAR)
+            }
+            Projection::Orthographic => {
+                // the height is scaled by the distance to the target
+                let height = 2.0 * (Rad::from(FIELD_OF_VIEW_Y).0 * 0.5).tan();
+                camera.set_orthographic_projection(height, Z_NEAR, Z_FAR);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StandardView {
+    Front,
+    Top,
+    Side,
+    Isometric,
+}
+
+impl StandardView {
+    pub const ALL: [StandardView; 4] = [
+        StandardView::Front,
+        StandardView::Top,
+        StandardView::Side,
+        StandardView::Isometric,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StandardView::Front => "Front",
+            StandardView::Top => "Top",
+            StandardView::Side => "Side",
+            StandardView::Isometric => "Isometric",
+        }
+    }
+
+    /// Direction from the target to the camera.
+    pub fn direction(&self) -> Vec3 {
+        match self {
+            StandardView::Front => vec3(0.0, 0.0, 1.0),
+            StandardView::Top => vec3(0.0, 1.0, 0.0),
+            StandardView::Side => vec3(1.0, 0.0, 0.0),
+            StandardView::Isometric => vec3(1.0, 1.0, 1.0).normalize(),
+        }
+    }
+
+    pub fn up(&self) -> Vec3 {
+        match self {
+            StandardView::Top => vec3(0.0, 0.0, -1.0),
+            _ => vec3(0.0, 1.0, 0.0),
+        }
+    }
+
+    /// The same distance from `target` as `pose`, looking from this side.
+    pub fn pose(&self, pose: &CameraPose) -> CameraPose {
+        let distance = (pose.position - pose.target).magnitude();
+
+        CameraPose {
+            position: pose.target + self.direction() * distance,
+            target: pose.target,
+            up: self.up(),
+        }
+    }
+}
+
+/// Moves `pose` along its view direction until `bounds` fit the viewport of
+/// the given aspect ratio, looking at their center. `None` for empty bounds.
+pub fn frame_bounds(
+    pose: &CameraPose,
+    bounds: &AxisAlignedBoundingBox,
+    aspect: f32,
+) -> Option<CameraPose> {
+    if bounds.is_empty() || bounds.is_infinite() {
+        return None;
+    }
+
+    let direction = normalized(pose.position - pose.target).unwrap_or(vec3(0.0, 0.0, 1.0));
+
+    // fit the bounding sphere in the narrower of the two fields of view
+    let half_height = Rad::from(FIELD_OF_VIEW_Y).0 * 0.5;
+    let half_width = (half_height.tan() * aspect).atan();
+    let radius = (bounds.size().magnitude() * 0.5).max(Z_NEAR) * FRAMING_MARGIN;
+    let distance = (radius / half_height.min(half_width).sin()).min(Z_FAR * 0.5);
+
+    Some(CameraPose {
+        position: bounds.center() + direction * distance,
+        target: bounds.center(),
+        up: pose.up,
+    })
+}
+
+/// An animated move between two poses, orbiting around the targets on the way.
+#[derive(Clone, Copy, Debug)]
+pub struct CameraTransition {
+    from: CameraPose,
+    to: CameraPose,
+    /// Seconds.
+    duration: f32,
+    elapsed: f32,
+}
+
+impl CameraTransition {
+    pub fn new(from: CameraPose, to: CameraPose, duration: f32) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn target(&self) -> CameraPose {
+        self.to
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Advances by `seconds`, returning the pose to show.
+    pub fn update(&mut self, seconds: f32) -> CameraPose {
+        self.elapsed = (self.elapsed + seconds).min(self.duration);
+
+        if self.is_finished() {
+            return self.to;
+        }
+
+        // eases in and out
+        let t = self.elapsed / self.duration;
+        let t = t * t * (3.0 - 2.0 * t);
+
+        let target = self.from.target + (self.to.target - self.from.target) * t;
+        let from_offset = self.from.position - self.from.target;
+        let to_offset = self.to.position - self.to.target;
+        let distance =
+            from_offset.magnitude() + (to_offset.magnitude() - from_offset.magnitude()) * t;
+        let direction = slerp(from_offset, to_offset, t);
+        let up = slerp(self.from.up, self.to.up, t);
+
+        // an up along the view direction leaves the view undefined, so keep
+        // the one the camera is heading for
+        let up = if up.cross(direction).magnitude2() < 1e-6 {
+            self.to.up
+        } else {
+            up
+        };
+
+        CameraPose {
+            position: target + direction * distance,
+            target,
+            up,
+        }
+    }
+}
+
+/// Spherical interpolation between the directions of `a` and `b`, giving a unit vector.
+fn slerp(a: Vec3, b: Vec3, t: f32) -> Vec3 {
+    let (Some(a), Some(b)) = (normalized(a), normalized(b)) else {
+        return normalized(a + (b - a) * t).unwrap_or(vec3(0.0, 0.0, 1.0));
+    };
+
+    let angle = a.dot(b).clamp(-1.0, 1.0).acos();
+
+    if angle < 1e-4 {
+        return (a + (b - a) * t).normalize();
+    }
+
+    // opposite directions turn around any perpendicular axis
+    let axis = a.cross(b);
+    let axis = if axis.magnitude2() < 1e-8 {
+        let other = if a.x.abs() < 0.9 {
+            vec3(1.0, 0.0, 0.0)
+        } else {
+            vec3(0.0, 1.0, 0.0)
+        };
+        a.cross(other).normalize()
+    } else {
+        axis.normalize()
+    };
+
+    // rotate `a` around the axis by a fraction of the angle
+    let theta = angle * t;
+    a * theta.cos() + axis.cross(a) * theta.sin() + axis * axis.dot(a) * (1.0 - theta.cos())
+}
+
+fn normalized(v: Vec3) -> Option<Vec3> {
+    let length = v.magnitude();
+    (length > f32::EPSILON).then(|| v / length)
+}
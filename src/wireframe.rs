@@ -46,3 +46,19 @@ pub fn edge_transform(p1: Vec3, p2: Vec3) -> Mat4 {
         ))
         * Mat4::from_nonuniform_scale((p1 - p2).magnitude(), 1.0, 1.0)
 }
+
+/// A thin cylinder of unit length along x, placed on edges by `edge_transformations`.
+pub fn edge_mesh() -> CpuMesh {
+    let mut cylinder = CpuMesh::cylinder(12);
+    cylinder
+        .transform(Mat4::from_nonuniform_scale(1.0, 0.007, 0.007))
+        .unwrap();
+    cylinder
+}
+
+/// A small sphere, placed on vertices by `vertex_transformations`.
+pub fn vertex_mesh() -> CpuMesh {
+    let mut sphere = CpuMesh::sphere(8);
+    sphere.transform(Mat4::from_scale(0.015)).unwrap();
+    sphere
+}